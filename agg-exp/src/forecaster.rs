@@ -5,9 +5,9 @@ use crate::loss::Loss;
 ///
 /// An ExpertPredictor provides methods for:
 /// 1. Given some 'N' experts' predictions, produce a prediction of
-/// type 'P'.
+///    type 'P'.
 /// 2. Given those same experts' predictions and an environmentally
-/// revealed 'P', compute new weights for the next prediction.
+///    revealed 'P', compute new weights for the next prediction.
 pub trait ExpertForecaster<P, const N: usize> {
     /// Given expert predictions, produce a prediction.
     fn predict(&self, experts: &[P; N]) -> P;
@@ -25,6 +25,7 @@ pub mod exp {
 
     /// Methods for calculating the value \eta.
     #[derive(Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum EtaMethod<W> {
         /// Horizon of 't' iterations; bound of \sqrt{(t/2)ln{N}}.
         KnownHorizon(usize),
@@ -37,18 +38,26 @@ pub mod exp {
 
     /// The Exponentially Weighted Average Forecaster (PLG - pg. 14).
     #[derive(Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(bound(
+            serialize = "W: serde::Serialize",
+            deserialize = "W: serde::Deserialize<'de>"
+        ))
+    )]
     pub struct EWAF<L, W, const N: usize> {
+        #[cfg_attr(feature = "serde", serde(with = "dst::serde_array"))]
         w: [W; N],
         eta: EtaMethod<W>,
         t: usize,
 
+        // The loss is a type-level choice; there's nothing to persist.
+        #[cfg_attr(feature = "serde", serde(skip))]
         phantom: PhantomData<L>,
     }
 
-    impl<L, const N: usize> EWAF<L, f32, N>
-    where
-        L: Loss<f32, f32>,
-    {
+    impl<L, const N: usize> EWAF<L, f32, N> {
         /// Construct the EWAF with the prescribed method.
         pub fn build(eta: EtaMethod<f32>) -> Self {
             Self {
@@ -75,12 +84,23 @@ pub mod exp {
                 EtaMethod::KnownLoss(l_ast) => sm(l_ast),
             }
         }
+
+        /// Advance the round and weigh each expert by its loss upon the revealed value.
+        fn reweigh<E>(&mut self, experts: &[E; N], revealed: &E)
+        where
+            L: Loss<E, f32>,
+        {
+            self.t += 1;
+            let eta = self.eta();
+
+            // w_{i,t} = w_{i,t-1} e^{-\eta \l(f_{i,t}, y_t)} - EECS598... "The Exponential Weights Algorithm".
+            for (w_i, p_i) in self.w.iter_mut().zip(experts) {
+                *w_i *= f32::exp(-eta * L::l(p_i, revealed));
+            }
+        }
     }
 
-    impl<L, const N: usize> Default for EWAF<L, f32, N>
-    where
-        L: Loss<f32, f32>,
-    {
+    impl<L, const N: usize> Default for EWAF<L, f32, N> {
         fn default() -> Self {
             Self {
                 w: [1.0; N],
@@ -111,13 +131,7 @@ pub mod exp {
         }
 
         fn update(&mut self, experts: &[f32; N], revealed: &f32) {
-            self.t += 1;
-            let eta = self.eta();
-
-            // w_{i,t} = w_{i,t-1} e^{-\eta \l(f_{i,t}, y_t)} - EECS598... "The Exponential Weights Algorithm".
-            for (w_i, p_i) in self.w.iter_mut().zip(experts) {
-                *w_i *= f32::exp(-1.0f32 * eta * L::l(p_i, revealed));
-            }
+            self.reweigh(experts, revealed);
 
             // The below matches the second equation in `predict()`.
 
@@ -131,6 +145,238 @@ pub mod exp {
         }
     }
 
+    // Vector predictions share a single set of weights across every dimension, so
+    // an expert is judged on the whole of its prediction.
+    impl<L, const N: usize, const D: usize> ExpertForecaster<[f32; D], N> for EWAF<L, f32, N>
+    where
+        L: Loss<[f32; D], f32>,
+    {
+        fn predict(&self, experts: &[[f32; D]; N]) -> [f32; D] {
+            // The scalar prediction, per dimension.
+            let total = self.w.iter().sum::<f32>();
+            core::array::from_fn(|d| {
+                self.w
+                    .iter()
+                    .zip(experts)
+                    .map(|(w, f)| w * f[d])
+                    .sum::<f32>()
+                    / total
+            })
+        }
+
+        fn update(&mut self, experts: &[[f32; D]; N], revealed: &[f32; D]) {
+            self.reweigh(experts, revealed);
+        }
+    }
+
     #[cfg(test)]
     mod test {}
 }
+
+/// Code related to the (Randomized) Weighted Majority algorithms, for experts whose
+/// predictions are discrete labels or actions rather than values to be averaged.
+pub mod wm {
+    use super::*;
+    use core::cell::RefCell;
+    use core::marker::PhantomData;
+    use rng::Rng;
+
+    /// Shrink each expert's weight by `\beta^{\l(f_{i,t}, y_t)}`; under `ZeroOne` loss
+    /// this is a factor of `\beta` for each mistake.
+    fn penalize<L, P, const N: usize>(w: &mut [f32; N], beta: f32, experts: &[P; N], revealed: &P)
+    where
+        L: Loss<P, f32>,
+    {
+        for (w_i, p_i) in w.iter_mut().zip(experts) {
+            *w_i *= f32::powf(beta, L::l(p_i, revealed));
+        }
+    }
+
+    /// The Weighted Majority algorithm (PLG - pg. 67); predict the label with the
+    /// greatest total weight behind it.
+    #[derive(Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct WeightedMajority<L, const N: usize> {
+        #[cfg_attr(feature = "serde", serde(with = "dst::serde_array"))]
+        w: [f32; N],
+        beta: f32,
+
+        #[cfg_attr(feature = "serde", serde(skip))]
+        phantom: PhantomData<L>,
+    }
+
+    impl<L, const N: usize> WeightedMajority<L, N> {
+        /// Construct WM with the penalty `beta` in `[0, 1)`; a `beta` of zero discards
+        /// an expert upon its first mistake (the halving algorithm).
+        pub fn build(beta: f32) -> Self {
+            assert!((0f32..1f32).contains(&beta));
+            Self {
+                w: [1.0; N],
+                beta,
+                phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<L, P, const N: usize> ExpertForecaster<P, N> for WeightedMajority<L, N>
+    where
+        L: Loss<P, f32>,
+        P: PartialEq + Clone,
+    {
+        fn predict(&self, experts: &[P; N]) -> P {
+            // The weight behind the label of each expert; ties go to the first.
+            let behind = |p: &P| -> f32 {
+                self.w
+                    .iter()
+                    .zip(experts)
+                    .filter(|(_, f)| *f == p)
+                    .map(|(w, _)| w)
+                    .sum()
+            };
+
+            experts
+                .iter()
+                .map(|p| (p, behind(p)))
+                .reduce(|max, e| if e.1 > max.1 { e } else { max })
+                .expect("There should be at least one expert.")
+                .0
+                .clone()
+        }
+
+        fn update(&mut self, experts: &[P; N], revealed: &P) {
+            penalize::<L, P, N>(&mut self.w, self.beta, experts, revealed);
+        }
+    }
+
+    /// The Randomized Weighted Majority algorithm (PLG - pg. 67); follow an expert
+    /// drawn in proportion to its weight.
+    #[derive(Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct RandomizedWeightedMajority<L, const N: usize> {
+        #[cfg_attr(feature = "serde", serde(with = "dst::serde_array"))]
+        w: [f32; N],
+        beta: f32,
+
+        // `predict` only borrows the forecaster, yet each draw advances the generator.
+        rng: RefCell<Rng>,
+
+        #[cfg_attr(feature = "serde", serde(skip))]
+        phantom: PhantomData<L>,
+    }
+
+    impl<L, const N: usize> RandomizedWeightedMajority<L, N> {
+        /// Construct RWM with the penalty `beta` in `[0, 1)`, and a `seed` so that its
+        /// draws are reproducible.
+        pub fn build(beta: f32, seed: u64) -> Self {
+            assert!((0f32..1f32).contains(&beta));
+            Self {
+                w: [1.0; N],
+                beta,
+                rng: RefCell::new(Rng::seed(seed)),
+                phantom: PhantomData,
+            }
+        }
+
+        /// The probability of following each expert in the next round.
+        pub fn distribution(&self) -> [f32; N] {
+            let total = self.w.iter().sum::<f32>();
+            core::array::from_fn(|i| self.w[i] / total)
+        }
+
+        /// The probability of predicting each expert's label in the next round; experts
+        /// in agreement share their mass.
+        pub fn action_distribution<P: PartialEq>(&self, experts: &[P; N]) -> [f32; N] {
+            let p = self.distribution();
+            core::array::from_fn(|i| {
+                p.iter()
+                    .zip(experts)
+                    .filter(|(_, f)| **f == experts[i])
+                    .map(|(p, _)| p)
+                    .sum()
+            })
+        }
+    }
+
+    impl<L, P, const N: usize> ExpertForecaster<P, N> for RandomizedWeightedMajority<L, N>
+    where
+        L: Loss<P, f32>,
+        P: Clone,
+    {
+        fn predict(&self, experts: &[P; N]) -> P {
+            let mut u = self.rng.borrow_mut().next_f32();
+            for (p, f) in self.distribution().iter().zip(experts) {
+                if u < *p {
+                    return f.clone();
+                }
+                u -= p;
+            }
+
+            // Rounding may leave `u` just beyond the final expert.
+            experts
+                .last()
+                .expect("There should be at least one expert.")
+                .clone()
+        }
+
+        fn update(&mut self, experts: &[P; N], revealed: &P) {
+            penalize::<L, P, N>(&mut self.w, self.beta, experts, revealed);
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::loss::ZeroOne;
+
+        #[test]
+        fn test_wm_majority() {
+            let mut wm = WeightedMajority::<ZeroOne, 3>::build(0.5);
+            assert_eq!(wm.predict(&['a', 'b', 'b']), 'b');
+
+            // Expert 0 alone is right, twice; it now outweighs the others combined.
+            for _ in 0..2 {
+                wm.update(&['a', 'b', 'b'], &'a');
+            }
+            assert_eq!(wm.predict(&['a', 'b', 'b']), 'a');
+        }
+
+        #[test]
+        fn test_wm_mistake_bound() {
+            // With a perfect expert among N, WM makes at most
+            // \frac{\ln{N}}{\ln{2 / (1 + \beta)}} mistakes.
+            const N: usize = 8;
+            let mut wm = WeightedMajority::<ZeroOne, N>::build(0.5);
+            let mut mistakes = 0;
+            for t in 0..64usize {
+                let y = t % 3;
+                let experts: [usize; N] =
+                    core::array::from_fn(|i| if i == 5 { y } else { (t + i) % 3 });
+                if wm.predict(&experts) != y {
+                    mistakes += 1;
+                }
+                wm.update(&experts, &y);
+            }
+            assert!(mistakes as f32 <= f32::ln(N as f32) / f32::ln(2f32 / 1.5f32));
+        }
+
+        #[test]
+        fn test_rwm_distribution() {
+            let mut rwm = RandomizedWeightedMajority::<ZeroOne, 3>::build(0.5, 7);
+            rwm.update(&[0u8, 1, 1], &1);
+            let p = rwm.distribution();
+            assert!((p[0] - 0.2f32).abs() < 1e-6 && (p[1] - 0.4f32).abs() < 1e-6);
+            assert_eq!(
+                rwm.action_distribution(&[0u8, 1, 1]),
+                [p[0], p[1] + p[2], p[1] + p[2]]
+            );
+
+            // The same seed draws the same actions; roughly in proportion to the weights.
+            let mut again = RandomizedWeightedMajority::<ZeroOne, 3>::build(0.5, 7);
+            again.update(&[0u8, 1, 1], &1);
+            let draws: [u8; 256] = core::array::from_fn(|_| rwm.predict(&[0u8, 1, 2]));
+            assert_eq!(draws, core::array::from_fn(|_| again.predict(&[0u8, 1, 2])));
+            let zeros = draws.iter().filter(|d| **d == 0).count() as f32 / 256f32;
+            assert!((zeros - 0.2f32).abs() < 0.1);
+        }
+    }
+}
@@ -61,6 +61,20 @@ where
     }
 }
 
+/// 0-1 loss, for discrete predictions: one for a mistake, zero otherwise.
+#[derive(Debug)]
+pub struct ZeroOne();
+
+impl<T: PartialEq> Loss<T, f32> for ZeroOne {
+    fn l(a: &T, b: &T) -> f32 {
+        if a == b {
+            0f32
+        } else {
+            1f32
+        }
+    }
+}
+
 /// MSE for 1-dimensional vectors.
 pub fn mse<const N: usize, T>(a: &[T; N], b: &[T; N]) -> T
 where
@@ -81,4 +95,10 @@ mod test {
         let r = 125f32;
         assert_eq!(L2::l(t, p), r);
     }
+
+    #[test]
+    fn test_zero_one() {
+        assert_eq!(ZeroOne::l(&'a', &'a'), 0f32);
+        assert_eq!(ZeroOne::l(&'a', &'b'), 1f32);
+    }
 }
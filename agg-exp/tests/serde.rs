@@ -0,0 +1,31 @@
+#![cfg(feature = "serde")]
+use agg_exp::forecaster::{
+    exp::{EtaMethod, EWAF},
+    ExpertForecaster,
+};
+use agg_exp::loss::L2;
+
+#[test]
+fn test_ewaf_round_trip() {
+    let mut ewaf = EWAF::<L2, f32, 3>::build(EtaMethod::KnownHorizon(16));
+    let p = [0.5f32, -0.5f32, 0.0f32];
+    for _ in 0..4 {
+        ewaf.update(&p, &0.0f32);
+    }
+
+    let json = serde_json::to_string(&ewaf).unwrap();
+    let restored: EWAF<L2, f32, 3> = serde_json::from_str(&json).unwrap();
+
+    // The restored forecaster should carry the same weights forward.
+    assert_eq!(restored.predict(&p), ewaf.predict(&p));
+    assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+}
+
+#[test]
+fn test_eta_method_round_trip() {
+    let json = serde_json::to_string(&EtaMethod::KnownLoss(2.5f32)).unwrap();
+    assert!(matches!(
+        serde_json::from_str::<EtaMethod<f32>>(&json).unwrap(),
+        EtaMethod::KnownLoss(l) if l == 2.5f32
+    ));
+}
@@ -0,0 +1,42 @@
+use agg_exp::forecaster::{exp::EWAF, ExpertForecaster};
+use agg_exp::loss::{Loss, L2};
+
+#[test]
+fn test_vector_position() {
+    // Three experts track a target moving along a helix in 3-D; the first is
+    // offset in x, the second in z, and the third lags a step behind.
+    let environment = |t: usize| -> [f32; 3] {
+        let t = t as f32 * 0.1f32;
+        [f32::cos(t), f32::sin(t), t]
+    };
+
+    let mut ewaf = EWAF::<L2, f32, 3>::default();
+    let mut cumulative_loss = [0.0f32; 4];
+
+    for t in 1..64 {
+        let truth = environment(t);
+        let mut a = truth;
+        a[0] += 0.5f32;
+        let mut b = truth;
+        b[2] -= 0.5f32;
+        let p = [a, b, environment(t - 1)];
+
+        let p_hat = ewaf.predict(&p);
+        ewaf.update(&p, &truth);
+
+        cumulative_loss[0] += L2::l(&p_hat, &truth);
+        for (l, p_i) in cumulative_loss[1..].iter_mut().zip(&p) {
+            *l += L2::l(p_i, &truth);
+        }
+    }
+
+    // A single set of weights, shared across dimensions, should do better than the
+    // offset experts and stay close to the best of them.
+    assert!(cumulative_loss[0] <= cumulative_loss[1]);
+    assert!(cumulative_loss[0] <= cumulative_loss[2]);
+    let best = cumulative_loss[1..]
+        .iter()
+        .cloned()
+        .fold(f32::MAX, f32::min);
+    assert!(cumulative_loss[0] <= best + 2f32 * f32::ln(3f32));
+}
@@ -6,7 +6,32 @@
 //! With an approximation, we can take some BBA `[?](set, f32)` and reduce it
 //! down to a known-length `[k](set, f32)`, allowing for reduce and so forth.
 use crate::container::heap::PriorityHeap;
-use crate::set::Set;
+use crate::container::hm::{Overflow, SummationHM};
+use crate::set::{Bitset, Set};
+use core::hash::Hash;
+use core::marker::PhantomData;
+use core::ops::{AddAssign, MulAssign};
+
+/// A mass that an `Approximation` can rank, rescale and merge; beyond `f32`, this
+/// lets the likes of `provenance::Tracked` carry information through approximation.
+pub trait Mass: Sized + AddAssign + MulAssign<f32> {
+    /// The mass as an `f32`.
+    fn mass(&self) -> f32;
+
+    /// Compute the term `m_1(X) m_2(Y)` of a combination, where `a` is the mass upon
+    /// `x` and `b` the mass upon `y`.
+    fn meet<S: Set>(x: &S, a: &Self, y: &S, b: &Self) -> Self;
+}
+
+impl Mass for f32 {
+    fn mass(&self) -> f32 {
+        *self
+    }
+
+    fn meet<S: Set>(_: &S, a: &Self, _: &S, b: &Self) -> Self {
+        a * b
+    }
+}
 
 /// An `Approximation` is a strategy for producing a BBA with a known input.
 pub trait Approximation<S: Set, T> {
@@ -17,19 +42,21 @@ pub trait Approximation<S: Set, T> {
 /// `KX` takes the `N` largest elements in producing the appproximation.
 pub struct KX;
 
-impl<S: Set> Approximation<S, f32> for KX {
-    fn approx<const N: usize>(bba: impl IntoIterator<Item = (S, f32)>) -> [Option<(S, f32)>; N] {
+impl<S: Set, T: Mass> Approximation<S, T> for KX {
+    fn approx<const N: usize>(bba: impl IntoIterator<Item = (S, T)>) -> [Option<(S, T)>; N] {
         // Utilize a PH to capture the N largest elements within the BBA.
-        let mut container = PriorityHeap::<N, (S, f32)>::default();
+        let f = |x: &(S, T)| x.1.mass();
+        let mut container = PriorityHeap::<N, (S, T)>::default();
         bba.into_iter().for_each(|x| {
-            let f = |x: &(S, f32)| x.1;
             container.insert_by_key(f, x);
         });
 
-        let mut buf = container.consume();
+        let mut buf = container.consume_sorted_by_key(f);
         // Rescale so that the resulting BBA sums to `1.0f32`.
-        let denom: f32 = buf.iter().flatten().map(|e| e.1).sum();
-        buf.iter_mut().flatten().for_each(|mem| mem.1 /= denom);
+        let denom: f32 = buf.iter().flatten().map(|e| e.1.mass()).sum();
+        buf.iter_mut()
+            .flatten()
+            .for_each(|mem| mem.1 *= 1f32 / denom);
 
         buf
     }
@@ -38,37 +65,166 @@ impl<S: Set> Approximation<S, f32> for KX {
 /// `Summarize` takes the `N-1` largest elements then unions all of the rest.
 pub struct Summarize;
 
-impl<S: Set> Approximation<S, f32> for Summarize {
-    fn approx<const N: usize>(bba: impl IntoIterator<Item = (S, f32)>) -> [Option<(S, f32)>; N] {
+impl<S: Set, T: Mass> Approximation<S, T> for Summarize {
+    fn approx<const N: usize>(bba: impl IntoIterator<Item = (S, T)>) -> [Option<(S, T)>; N] {
         // Utilize a PH to capture the N largest elements within the BBA; those that are
-        // evicted are merged together. NOTE: We technically want to store (N-1) elements,
-        // but const generics make this difficult -- we'll handle this later.
-        let mut container = PriorityHeap::<N, (S, f32)>::default();
-        let mut merged = (S::EMPTY, 0.0f32);
+        // evicted are merged together.
+        let f = |x: &(S, T)| x.1.mass();
+        let mut container = PriorityHeap::<N, (S, T)>::default();
+        let mut merged: Option<(S, T)> = None;
+        let union = |mut a: (S, T), b: (S, T)| {
+            a.1 += b.1;
+            (S::cup(&a.0, &b.0), a.1)
+        };
 
         for elem in bba {
-            let f = |x: &(S, f32)| x.1;
             if let Some(evicted) = container.insert_by_key(f, elem) {
-                merged = (S::cup(&merged.0, &evicted.0), merged.1 + evicted.1);
+                merged = Some(match merged {
+                    Some(m) => union(m, evicted),
+                    None => evicted,
+                });
             }
         }
 
-        let mut buf = container.consume();
-        // Back to N vs (N-1) -- merge the `merged` into the last element of the arr.
-        if let Some(mem) = buf.iter_mut().find(|x| x.is_none()) {
-            *mem = Some(merged);
-        } else {
-            // TODO: It might be wiser to find the smallest element, which isn't guaranteed
-            // to be the last element.
-            let last = buf.get(N - 1).unwrap().as_ref().unwrap();
-            merged = (S::cup(&merged.0, &last.0), merged.1 + last.1);
-            *buf.get_mut(N - 1).unwrap() = Some(merged);
+        // Back to N vs (N-1) -- if anything was evicted the heap is full, so the
+        // smallest retained element joins the merged one to make room.
+        if let Some(m) = merged {
+            let smallest = container.pop_min_by_key(f).unwrap();
+            container.insert_by_key(f, union(m, smallest));
         }
 
-        buf
+        container.consume_sorted_by_key(f)
     }
 }
 
+/// `KAdditive` caps the cardinality of the focal sets at `K`, then approximates with
+/// `A` to cap their number. The mass of a larger focal set `B` is split evenly over its
+/// `\binom{|B|}{K}` subsets of size `K`, which leaves the pignistic probability as is.
+///
+/// The pieces are summed into an accumulator of `N * N` focal sets before `A` sees
+/// them, and no more than that many subsets are visited per focal set: beyond it, `B`
+/// is instead partitioned into blocks of `K`, each given mass in proportion to its size,
+/// which is just as pignistic-consistent. Should the accumulator fill, a piece is
+/// unioned onto the least entry whose union with it stays within `K`; failing that, its
+/// mass is added onto the least entry as is, so that the cap always holds.
+///
+/// Should `A` union focal sets past `K`, as `Summarize` may, those are partitioned
+/// and folded back in the same way.
+pub struct KAdditive<const K: usize, A = KX>(PhantomData<A>);
+
+impl<const K: usize, S, T, A> Approximation<S, T> for KAdditive<K, A>
+where
+    S: Bitset + Hash + Eq + Clone,
+    T: Mass + Clone,
+    A: Approximation<S, T>,
+{
+    fn approx<const N: usize>(bba: impl IntoIterator<Item = (S, T)>) -> [Option<(S, T)>; N] {
+        assert!(K > 0);
+        if N == 0 {
+            return A::approx(core::iter::empty());
+        }
+
+        let mut map: SummationHM<N, N, S, T> = SummationHM::default();
+        for (b, m) in bba {
+            split::<K, S, T>(b, m, N * N, |k, v| {
+                insert_capped::<K, N, N, S, T>(&mut map, k, v)
+            });
+        }
+
+        let approx: [Option<(S, T)>; N] = A::approx(map.consume());
+        if approx.iter().flatten().all(|x| x.0.card() <= K) {
+            return approx;
+        }
+
+        let mut map: SummationHM<1, N, S, T> = SummationHM::default();
+        for (b, m) in approx.into_iter().flatten() {
+            split::<K, S, T>(b, m, 0, |k, v| {
+                insert_capped::<K, 1, N, S, T>(&mut map, k, v)
+            });
+        }
+        KX::approx(map.consume())
+    }
+}
+
+/// Split the mass `m` of `b` over its subsets of size `K`, should there be no more than
+/// `limit` of them, or else over a partition of `b` into blocks of `K`.
+fn split<const K: usize, S, T>(b: S, m: T, limit: usize, mut insert: impl FnMut(S, T))
+where
+    S: Bitset,
+    T: Mass + Clone,
+{
+    let c = b.card();
+    if c <= K {
+        return insert(b, m);
+    }
+
+    let share = binomial(c, K);
+    if share <= limit as f32 {
+        let mut piece = m.clone();
+        piece *= 1f32 / share;
+
+        // Walk the positions of each `K`-subset within `B` in lexicographic order.
+        let mut pos: [usize; K] = core::array::from_fn(|i| i);
+        loop {
+            insert(pick(&b, pos), piece.clone());
+            let Some(i) = (0..K).rev().find(|i| pos[*i] < c - K + i) else {
+                break;
+            };
+            pos[i] += 1;
+            (i + 1..K).for_each(|j| pos[j] = pos[j - 1] + 1);
+        }
+    } else {
+        for start in (0..c).step_by(K) {
+            let len = K.min(c - start);
+            let mut piece = m.clone();
+            piece *= len as f32 / c as f32;
+            insert(pick(&b, start..start + len), piece);
+        }
+    }
+}
+
+/// Insert a focal set of no more than `K` elements into `map`, keeping to `K` should
+/// it be full; see `KAdditive`.
+fn insert_capped<const K: usize, const R: usize, const C: usize, S, T>(
+    map: &mut SummationHM<R, C, S, T>,
+    k: S,
+    v: T,
+) where
+    S: Bitset + Hash + Eq + Clone,
+    T: Mass,
+{
+    let fits = |x: &S, y: &S| S::cup(x, y).card() <= K;
+    let piece = k.clone();
+    map.insert_or_by_key(
+        k,
+        v,
+        |x| (!fits(&x.0, &piece), x.1.mass()),
+        |entry, new| {
+            if fits(&entry.0, &new.0) {
+                entry.0 = S::cup(&entry.0, &new.0);
+            }
+            entry.1 += new.1;
+            Ok::<(), Overflow>(())
+        },
+    )
+    .expect("The absorb never fails.");
+}
+
+/// Collect the elements of `b` at the (ascending) positions `pos` within it.
+fn pick<S: Bitset>(b: &S, pos: impl IntoIterator<Item = usize>) -> S {
+    let mut pos = pos.into_iter().peekable();
+    (0..S::BITS)
+        .filter(|i| S::singleton(*i).is_subset(b))
+        .enumerate()
+        .filter(|(j, _)| pos.next_if_eq(j).is_some())
+        .fold(S::EMPTY, |acc, (_, i)| S::cup(&acc, &S::singleton(i)))
+}
+
+/// Compute `\binom{n}{k}`.
+fn binomial(n: usize, k: usize) -> f32 {
+    (0..k).fold(1f32, |acc, i| acc * (n - i) as f32 / (i + 1) as f32)
+}
+
 #[cfg(test)]
 mod tests {
     const N: usize = 3;
@@ -132,6 +288,18 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_summarize_merges_smallest() {
+            let input = [(1usize, 0.40f32), (2, 0.10f32), (4, 0.30f32), (8, 0.20f32)];
+            let output = [(1usize, 0.40f32), (4, 0.30f32), (2 | 8, 0.10f32 + 0.20f32)];
+
+            let approx = Summarize::approx::<N>(input);
+            for elem in approx.iter().flatten() {
+                assert!(output.contains(elem));
+            }
+            assert_eq!(approx[0], Some((1usize, 0.40f32))); // Descending by mass.
+        }
+
         #[test]
         fn test_summarize_incomplete() {
             let input = [(1usize, 0.50f32), (3, 0.50f32)];
@@ -142,4 +310,82 @@ mod tests {
             }
         }
     }
+
+    mod k_additive {
+        use super::super::{Approximation, KAdditive, Summarize, KX};
+        use crate::dst::betp;
+        use crate::set::Bitset;
+
+        const TOL: f32 = 0.001;
+
+        #[test]
+        fn test_k_additive_splits() {
+            let input = [(0b0001usize, 0.4f32), (0b0111, 0.6f32)];
+            let approx = KAdditive::<2>::approx::<4>(input);
+
+            // 0b0111 splits into 0b0011, 0b0101 and 0b0110 at 0.2 apiece.
+            for elem in approx.iter().flatten() {
+                assert!(elem.0.count_ones() <= 2);
+            }
+            assert!(approx
+                .iter()
+                .flatten()
+                .any(|x| x.0 == 0b0110 && (x.1 - 0.2f32).abs() < TOL));
+
+            let out: [(usize, f32); 4] = approx.map(|x| x.unwrap_or((0, 0f32)));
+            for q in [0b0001usize, 0b0010, 0b0100, 0b1000] {
+                assert!((betp(&out, &q) - betp(&input, &q)).abs() < TOL);
+            }
+        }
+
+        #[test]
+        fn test_k_additive_merges() {
+            // Both focal sets contribute to {0, 1}, which is summed.
+            let input = [(0b0111usize, 0.6f32), (0b1011, 0.4f32)];
+            let approx = KAdditive::<2, Summarize>::approx::<8>(input);
+            let ab = approx.iter().flatten().find(|x| x.0 == 0b0011).unwrap();
+            assert!((ab.1 - (0.2f32 + 0.4f32 / 3f32)).abs() < TOL);
+        }
+
+        #[test]
+        fn test_k_additive_both_limits() {
+            let input = [(0b1111usize, 1f32)];
+            let approx = KAdditive::<1, KX>::approx::<2>(input);
+            for elem in approx.iter().flatten() {
+                assert_eq!(elem.0.count_ones(), 1);
+            }
+            assert!((approx.iter().flatten().map(|x| x.1).sum::<f32>() - 1f32).abs() < TOL);
+        }
+
+        #[test]
+        fn test_k_additive_overflow() {
+            // Six pieces of size 2 into an accumulator of four.
+            let input = [(0b000111usize, 0.5f32), (0b111000, 0.5f32)];
+            let approx = KAdditive::<2, KX>::approx::<2>(input);
+            assert!(approx.iter().flatten().all(|x| x.0.card() <= 2));
+            assert!((approx.iter().flatten().map(|x| x.1).sum::<f32>() - 1f32).abs() < TOL);
+
+            // `Summarize` unions what it evicts, past the cap.
+            let input = [(0b0001usize, 0.4f32), (0b0110, 0.3f32), (0b1000, 0.3f32)];
+            let approx = KAdditive::<2, Summarize>::approx::<2>(input);
+            assert!(approx.iter().flatten().all(|x| x.0.card() <= 2));
+            assert!((approx.iter().flatten().map(|x| x.1).sum::<f32>() - 1f32).abs() < TOL);
+        }
+
+        #[test]
+        fn test_k_additive_wide_frame() {
+            // 72 elements; far too many 8-subsets to visit, so the frame is partitioned.
+            type S = [u8; 9];
+            let input = [(S::singleton(0), 0.4f32), ([0xffu8; 9], 0.6f32)];
+            let approx = KAdditive::<8>::approx::<16>(input);
+
+            let out: Vec<(S, f32)> = approx.iter().flatten().cloned().collect();
+            assert_eq!(out.len(), 10);
+            assert!(out.iter().all(|x| x.0.card() <= 8));
+            for q in [0, 1, 71] {
+                let q = S::singleton(q);
+                assert!((betp(&out, &q) - betp(&input, &q)).abs() < TOL);
+            }
+        }
+    }
 }
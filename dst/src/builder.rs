@@ -0,0 +1,371 @@
+//! Constructing BBAs from the outputs of probabilistic sensors.
+//!
+//! Class `i` of a sensor's output corresponds to the singleton `S::singleton(i)`;
+//! each builder produces a fixed-length BBA that `comb_approx` accepts directly.
+use crate::approx::Approximation;
+use crate::comb::{OverflowPolicy, Union};
+use crate::container::hm::SummationHM;
+use crate::set::Bitset;
+use core::hash::Hash;
+use rng::Rng;
+
+/// Order the indices of `x` by decreasing value.
+fn descending<const K: usize>(x: &[f32; K]) -> [usize; K] {
+    let mut idx: [usize; K] = core::array::from_fn(|i| i);
+    idx.sort_unstable_by(|a, b| x[*b].total_cmp(&x[*a]));
+    idx
+}
+
+/// Build the consonant BBA whose nested focal sets `A_1 \subset ... \subset A_K`
+/// add classes in order of decreasing `x`, with `m(A_k) = g(k, x_{(k)} - x_{(k+1)})`
+/// where `x_{(K+1)} = 0`.
+pub(crate) fn consonant<const K: usize, S: Bitset + Clone>(
+    x: &[f32; K],
+    g: impl Fn(usize, f32) -> f32,
+) -> [(S, f32); K] {
+    let idx = descending(x);
+    let mut focal = S::EMPTY;
+    core::array::from_fn(|k| {
+        focal = S::cup(&focal, &S::singleton(idx[k]));
+        let next = idx.get(k + 1).map_or(0f32, |j| x[*j]);
+        (focal.clone(), g(k + 1, x[idx[k]] - next))
+    })
+}
+
+/// Shafer's likelihood-based BBA: the consonant BBA with `pl(\{\theta_i\})`
+/// proportional to the likelihood `l_i`, normalized so the most likely class is
+/// fully plausible.
+pub fn likelihood<const K: usize, S: Bitset + Clone>(l: &[f32; K]) -> [(S, f32); K] {
+    assert!(l.iter().all(|x| *x >= 0f32));
+    let max = l.iter().copied().fold(0f32, f32::max);
+    assert!(max > 0f32, "Should have a non-zero likelihood.");
+
+    consonant(l, |_, d| d / max)
+}
+
+/// The inverse pignistic transform: the least committed BBA whose pignistic
+/// probability is `p`, with `m(A_k) = k (p_{(k)} - p_{(k+1)})`.
+pub fn inverse_pignistic<const K: usize, S: Bitset + Clone>(p: &[f32; K]) -> [(S, f32); K] {
+    assert!(p.iter().all(|x| *x >= 0f32));
+    consonant(p, |k, d| k as f32 * d)
+}
+
+/// A simple support function upon the most probable class: `m(\{\theta\}) = p_\theta`
+/// and the rest upon `frame`, should `p_\theta` reach `threshold`; otherwise the BBA
+/// is vacuous.
+pub fn simple_support<const K: usize, S: Bitset + Clone>(
+    p: &[f32; K],
+    frame: &S,
+    threshold: f32,
+) -> [(S, f32); 2] {
+    let (i, p_i) = p
+        .iter()
+        .copied()
+        .enumerate()
+        .reduce(|max, e| if e.1 > max.1 { e } else { max })
+        .expect("Should have at least one class.");
+
+    let m = if p_i >= threshold { p_i } else { 0f32 };
+    [(S::singleton(i), m), (frame.clone(), 1f32 - m)]
+}
+
+/// A simple support function from a classifier's confusion matrix, where
+/// `confusion[i][j]` counts the samples of class `i` labelled `j`: the predicted class
+/// is supported by the precision of that label, and the rest is upon the frame.
+pub fn confusion_precision<const K: usize, S: Bitset + Clone>(
+    confusion: &[[f32; K]; K],
+    predicted: usize,
+) -> [(S, f32); 2] {
+    let labelled: f32 = confusion.iter().map(|row| row[predicted]).sum();
+    assert!(
+        labelled > 0f32,
+        "Should have validated the predicted label."
+    );
+
+    let frame = (0..K).fold(S::EMPTY, |acc, i| S::cup(&acc, &S::singleton(i)));
+    let m = confusion[predicted][predicted] / labelled;
+    [(S::singleton(predicted), m), (frame, 1f32 - m)]
+}
+
+/// A likelihood-based BBA from a classifier's confusion matrix, where
+/// `confusion[i][j]` counts the samples of class `i` labelled `j`: the likelihood of
+/// class `i` is the rate at which it's given the predicted label (see `likelihood`).
+pub fn confusion_likelihood<const K: usize, S: Bitset + Clone>(
+    confusion: &[[f32; K]; K],
+    predicted: usize,
+) -> [(S, f32); K] {
+    let l: [f32; K] = core::array::from_fn(|i| {
+        let total: f32 = confusion[i].iter().sum();
+        if total > 0f32 {
+            confusion[i][predicted] / total
+        } else {
+            0f32
+        }
+    });
+
+    likelihood(&l)
+}
+
+/// Walley's imprecise Dirichlet model from class counts: with `n` observations and a
+/// prior strength `s`, `m(\{\theta_i\}) = n_i / (n + s)` and `m(\Theta) = s / (n + s)`.
+/// The frame takes a `K + 1`-th focal set, so this produces an iterator rather than
+/// an array.
+///
+/// With two classes and `s = 1`, this is exactly Dempster's multinomial model for the
+/// next observation; beyond two, see `dempster_multinomial`.
+pub fn idm<const K: usize, S: Bitset + Clone>(
+    counts: &[u32; K],
+    s: f32,
+) -> impl Iterator<Item = (S, f32)> {
+    assert!(s > 0f32);
+    let n = counts.iter().map(|x| *x as f32).sum::<f32>() + s;
+    let frame = (0..K).fold(S::EMPTY, |acc, i| S::cup(&acc, &S::singleton(i)));
+    let counts = *counts;
+
+    (0..K)
+        .map(move |i| (S::singleton(i), counts[i] as f32 / n))
+        .chain(core::iter::once((frame, s / n)))
+}
+
+/// Dempster's multinomial model from class counts: the BBA of the class of the next
+/// observation, estimated from `draws` samples of a Gibbs sampler after `burn_in`
+/// sweeps, and approximated by `A` to `N` focal sets.
+///
+/// Each observation is a uniform point `u` of the simplex, and is of class
+/// `\arg\min_j u_j / \theta_j`; the points must agree upon some `\theta`, which
+/// comes down to a system of difference constraints upon `\log \theta`, so the state
+/// is the least ratio `u_j / u_k` over the points of each class `k`. A sweep redraws the
+/// points of each class given the rest, which lie uniformly within a simplex; the
+/// classes that a fresh point could then be of make up the focal set of the draw.
+///
+/// A. P. Dempster, "New methods for reasoning towards posterior distributions based
+/// on sample data", Annals of Mathematical Statistics, 1966.
+/// P. E. Jacob, R. Gong, P. T. Edlefsen, A. P. Dempster, "A Gibbs sampler for a class
+/// of random convex polytopes", Journal of the American Statistical Association, 2021.
+pub fn dempster_multinomial<const K: usize, const N: usize, S, A>(
+    counts: &[u32; K],
+    burn_in: usize,
+    draws: usize,
+    rng: &mut Rng,
+) -> [Option<(S, f32)>; N]
+where
+    S: Bitset + Hash + Eq + Clone,
+    A: Approximation<S, f32>,
+{
+    assert!(draws > 0);
+
+    // Draw the points of class `k` within the simplex of vertices `e_j`, `j \neq k`,
+    // and `\eta / \sum \eta`, keeping the log of the least ratio `u_j / u_k`.
+    let redraw = |k: usize, eta: &[f32; K], rng: &mut Rng| -> [f32; K] {
+        let total: f32 = eta.iter().sum();
+        let mut row = [f32::INFINITY; K];
+        for _ in 0..counts[k] {
+            let e_k = exponential(rng);
+            for j in (0..K).filter(|j| *j != k) {
+                row[j] = row[j].min(eta[j] + total * exponential(rng) / e_k);
+            }
+        }
+        row.map(libm::logf)
+    };
+
+    // Start from the points of a uniform `\theta`, which they all agree upon.
+    let mut w: [[f32; K]; K] = core::array::from_fn(|k| redraw(k, &[1f32; K], rng));
+    let mut map: SummationHM<N, N, S, f32> = SummationHM::default();
+
+    for t in 0..burn_in + draws {
+        for k in (0..K).filter(|k| counts[*k] > 0) {
+            let d = shortest(&w, Some(k));
+            let eta = core::array::from_fn(|j| libm::expf(-d[j][k]));
+            w[k] = redraw(k, &eta, rng);
+        }
+
+        if t < burn_in {
+            continue;
+        }
+
+        // A fresh point is of class `k` for some `\theta` iff no cycle through `k` turns
+        // negative, in ratios `e_j / e_k` of exponentials.
+        let d = shortest(&w, None);
+        let e: [f32; K] = core::array::from_fn(|_| exponential(rng));
+        let focal = (0..K)
+            .filter(|k| (0..K).all(|j| j == *k || e[j] >= e[*k] * libm::expf(-d[j][*k])))
+            .fold(S::EMPTY, |acc, k| S::cup(&acc, &S::singleton(k)));
+        map.insert_or(focal, 1f32 / draws as f32, Union::absorb)
+            .expect("`Union` always absorbs.");
+    }
+
+    A::approx(map.consume())
+}
+
+/// Compute the shortest paths between every pair of classes, where the edge `k \to j`
+/// has weight `w[k][j]`; the edges out of `skip` are left out.
+fn shortest<const K: usize>(w: &[[f32; K]; K], skip: Option<usize>) -> [[f32; K]; K] {
+    let mut d: [[f32; K]; K] = core::array::from_fn(|i| {
+        core::array::from_fn(|j| match (i == j, skip == Some(i)) {
+            (true, _) => 0f32,
+            (false, true) => f32::INFINITY,
+            (false, false) => w[i][j],
+        })
+    });
+
+    // Floyd-Warshall.
+    for m in 0..K {
+        for i in 0..K {
+            for j in 0..K {
+                d[i][j] = d[i][j].min(d[i][m] + d[m][j]);
+            }
+        }
+    }
+    d
+}
+
+/// Draw from the exponential distribution of unit rate.
+fn exponential(rng: &mut Rng) -> f32 {
+    -libm::logf(1f32 - rng.next_f32())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::KX;
+    use crate::comb::Dempster;
+    use crate::dst::{bel, betp, comb_approx, pl};
+
+    const TOL: f32 = 0.001;
+
+    #[test]
+    fn test_likelihood() {
+        let bba: [(usize, f32); 3] = likelihood(&[0.2, 0.8, 0.4]);
+        assert_eq!(bba[0].0, 0b010);
+        assert_eq!(bba[1].0, 0b110);
+        assert_eq!(bba[2].0, 0b111);
+
+        // The plausibility of each singleton is the normalized likelihood.
+        assert!((pl(&bba, &0b001) - 0.25f32).abs() < TOL);
+        assert!((pl(&bba, &0b010) - 1.0f32).abs() < TOL);
+        assert!((pl(&bba, &0b100) - 0.5f32).abs() < TOL);
+        assert!((bel(&bba, &0b111) - 1.0f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_inverse_pignistic() {
+        let p = [0.1f32, 0.6, 0.3];
+        let bba: [(usize, f32); 3] = inverse_pignistic(&p);
+
+        // m(\{1\}) = 0.3, m(\{1, 2\}) = 2 * 0.2, m(\Theta) = 3 * 0.1.
+        assert!((bba[0].1 - 0.3f32).abs() < TOL);
+        assert!((bba[1].1 - 0.4f32).abs() < TOL);
+        assert!((bba[2].1 - 0.3f32).abs() < TOL);
+        for (i, p_i) in p.iter().enumerate() {
+            assert!((betp(&bba, &(1 << i)) - p_i).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_simple_support() {
+        let frame = 0b111usize;
+        let confident: [(usize, f32); 2] = simple_support(&[0.05, 0.9, 0.05], &frame, 0.5);
+        assert_eq!(confident[0], (0b010, 0.9f32));
+        assert!((bel(&confident, &frame) - 1.0f32).abs() < TOL);
+
+        let unsure: [(usize, f32); 2] = simple_support(&[0.3, 0.4, 0.3], &frame, 0.5);
+        assert!((bel(&unsure, &frame) - 1.0f32).abs() < TOL);
+        assert!(bel(&unsure, &0b010).abs() < TOL);
+    }
+
+    #[test]
+    fn test_fusion() {
+        // A softmax and a likelihood sensor are fused directly.
+        let a: [(usize, f32); 3] = inverse_pignistic(&[0.7, 0.2, 0.1]);
+        let b: [(usize, f32); 3] = likelihood(&[0.9, 0.3, 0.1]);
+        let bba = comb_approx::<4, usize, f32, KX, Dempster>([a, b]).unwrap();
+        assert!(bel(&bba, &0b001) > 0.4f32);
+        assert!((bel(&bba, &0b111) - 1.0f32).abs() < TOL);
+    }
+
+    // Rows are the true class, columns the label.
+    const CONFUSION: [[f32; 3]; 3] = [[80., 15., 5.], [10., 60., 30.], [0., 10., 90.]];
+
+    #[test]
+    fn test_confusion_precision() {
+        // 60 of the 85 samples labelled 1 are of class 1.
+        let bba: [(usize, f32); 2] = confusion_precision(&CONFUSION, 1);
+        assert_eq!(bba[0].0, 0b010);
+        assert!((bel(&bba, &0b010) - 60f32 / 85f32).abs() < TOL);
+        assert!((bel(&bba, &0b111) - 1.0f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_confusion_likelihood() {
+        // Labelled 2: P(2 | 0) = 0.05, P(2 | 1) = 0.3, P(2 | 2) = 0.9.
+        let bba: [(usize, f32); 3] = confusion_likelihood(&CONFUSION, 2);
+        assert!((pl(&bba, &0b100) - 1.0f32).abs() < TOL);
+        assert!((pl(&bba, &0b010) - 0.3f32 / 0.9f32).abs() < TOL);
+        assert!((pl(&bba, &0b001) - 0.05f32 / 0.9f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_confusion_fusion() {
+        // Two classifiers disagree; the more precise label wins out.
+        let other = [[50f32, 50., 0.], [5., 95., 0.], [0., 50., 50.]];
+        let a: [(usize, f32); 2] = confusion_precision(&CONFUSION, 2);
+        let b: [(usize, f32); 2] = confusion_precision(&other, 1);
+        let bba = comb_approx::<4, usize, f32, KX, Dempster>([a, b]).unwrap();
+        assert!(betp(&bba, &0b100) > betp(&bba, &0b010));
+    }
+
+    #[test]
+    fn test_idm() {
+        let bba: [(usize, f32); 4] = {
+            let mut iter = idm(&[6, 3, 1], 2f32);
+            core::array::from_fn(|_| iter.next().unwrap())
+        };
+        assert!((bba[3].1 - 2f32 / 12f32).abs() < TOL);
+        assert!((bel(&bba, &0b001) - 0.5f32).abs() < TOL);
+        assert!((pl(&bba, &0b001) - 8f32 / 12f32).abs() < TOL);
+
+        // Without data, the BBA is vacuous.
+        let vacuous: [(usize, f32); 4] = {
+            let mut iter = idm(&[0, 0, 0], 2f32);
+            core::array::from_fn(|_| iter.next().unwrap())
+        };
+        assert!(bel(&vacuous, &0b011).abs() < TOL);
+        assert!((bel(&vacuous, &0b111) - 1f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_idm_two_classes() {
+        // Dempster's model: seven heads and three tails give [7 / 11, 8 / 11] for heads.
+        let bba: [(usize, f32); 3] = {
+            let mut iter = idm(&[7, 3], 1f32);
+            core::array::from_fn(|_| iter.next().unwrap())
+        };
+        assert!((bel(&bba, &0b01) - 7f32 / 11f32).abs() < TOL);
+        assert!((pl(&bba, &0b01) - 8f32 / 11f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_dempster_multinomial() {
+        let mut rng = Rng::seed(7);
+        let tol = 0.02;
+
+        // Two classes agree with `idm` for `s = 1`.
+        let bba = dempster_multinomial::<2, 4, usize, KX>(&[7, 3], 100, 20000, &mut rng);
+        let bba: Vec<(usize, f32)> = bba.into_iter().flatten().collect();
+        assert!((bel(&bba, &0b01) - 7f32 / 11f32).abs() < tol);
+        assert!((pl(&bba, &0b01) - 8f32 / 11f32).abs() < tol);
+
+        // Beyond two, the plausibility of a class is still `(n_k + 1) / (n + 1)`, but
+        // mass falls upon pairs of classes too.
+        let bba = dempster_multinomial::<3, 8, usize, KX>(&[6, 3, 1], 100, 20000, &mut rng);
+        let bba: Vec<(usize, f32)> = bba.into_iter().flatten().collect();
+        for (q, n_k) in [(0b001usize, 6f32), (0b010, 3f32), (0b100, 1f32)] {
+            assert!((pl(&bba, &q) - (n_k + 1f32) / 11f32).abs() < tol);
+        }
+        assert!(bba.iter().any(|x| x.0.count_ones() == 2 && x.1 > tol));
+
+        // Without data, the BBA is vacuous.
+        let vacuous = dempster_multinomial::<3, 8, usize, KX>(&[0, 0, 0], 10, 100, &mut rng);
+        assert_eq!(vacuous[0], Some((0b111, 1f32)));
+    }
+}
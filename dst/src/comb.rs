@@ -1,29 +1,88 @@
 //! Combination rules for Dempster-Shafer Theory.
-use crate::container::em::SummationEM;
+use crate::approx::Mass;
+use crate::container::hm::{Overflow, SummationHM};
 use crate::set::Set;
+use core::hash::Hash;
+use core::marker::PhantomData;
+
+/// Errors that can occur when combining BBAs.
+#[derive(Debug, PartialEq)]
+pub enum CombError {
+    /// More distinct focal elements were produced than the accumulator could hold.
+    Overflow,
+    /// The BBAs are in total conflict; there's nothing left to normalize.
+    Conflict,
+}
+
+impl From<Overflow> for CombError {
+    fn from(_: Overflow) -> Self {
+        CombError::Overflow
+    }
+}
 
 /// A rule that defines how BBAs should be merged.
 pub trait CombRule<S: Set, T> {
-    /// Combine two BBAs into one.
-    fn comb<const N: usize>(
+    /// Combine two BBAs, of `N` and `M` elements, into one.
+    fn comb<const N: usize, const M: usize>(
         a: &[Option<(S, T)>; N],
-        b: &[Option<(S, T)>; N],
-    ) -> impl Iterator<Item = (S, T)>;
+        b: &[Option<(S, T)>; M],
+    ) -> Result<impl Iterator<Item = (S, T)>, CombError>;
+}
+
+/// What a combination rule does should more distinct focal sets arise than its
+/// accumulator holds.
+pub trait OverflowPolicy<S, T> {
+    /// Fold the focal set `new` into `smallest`, the entry with the least mass, or
+    /// give up.
+    fn absorb(smallest: &mut (S, T), new: (S, T)) -> Result<(), CombError>;
+}
+
+/// Give up with a `CombError::Overflow`.
+pub struct Reject;
+
+impl<S, T> OverflowPolicy<S, T> for Reject {
+    fn absorb(_: &mut (S, T), _: (S, T)) -> Result<(), CombError> {
+        Err(CombError::Overflow)
+    }
+}
+
+/// Add the mass onto the smallest entry, leaving its focal set as is.
+pub struct MergeSmallest;
+
+impl<S, T: Mass> OverflowPolicy<S, T> for MergeSmallest {
+    fn absorb(smallest: &mut (S, T), new: (S, T)) -> Result<(), CombError> {
+        smallest.1 += new.1;
+        Ok(())
+    }
+}
+
+/// Union the focal set with the smallest entry, as `Summarize` would.
+pub struct Union;
+
+impl<S: Set, T: Mass> OverflowPolicy<S, T> for Union {
+    fn absorb(smallest: &mut (S, T), new: (S, T)) -> Result<(), CombError> {
+        smallest.0 = S::cup(&smallest.0, &new.0);
+        smallest.1 += new.1;
+        Ok(())
+    }
 }
 
-/// Dempter's original rule.
+/// Dempter's original rule. There are at most `N * M` distinct intersections, so the
+/// accumulator can't overflow; should the BBAs be in total conflict, there's nothing
+/// to normalize and this is a `CombError::Conflict`.
 pub struct Dempster;
 
-impl<S> CombRule<S, f32> for Dempster
+impl<S, T> CombRule<S, T> for Dempster
 where
-    S: Set,
+    S: Set + Hash + Eq,
+    T: Mass,
 {
-    fn comb<const N: usize>(
-        a: &[Option<(S, f32)>; N],
-        b: &[Option<(S, f32)>; N],
-    ) -> impl Iterator<Item = (S, f32)> {
+    fn comb<const N: usize, const M: usize>(
+        a: &[Option<(S, T)>; N],
+        b: &[Option<(S, T)>; M],
+    ) -> Result<impl Iterator<Item = (S, T)>, CombError> {
         let mut conflict = 0.0f32; // K.
-        let mut map: SummationEM<N, S, f32> = SummationEM::default();
+        let mut map: SummationHM<N, M, S, T> = SummationHM::default();
 
         for (j, k) in a
             .iter()
@@ -31,16 +90,154 @@ where
             .flat_map(|j| b.iter().flatten().map(move |k| (j, k)))
         {
             let j_cap_k = S::cap(&j.0, &k.0);
-            let j_mul_k = j.1 * k.1;
 
             if j_cap_k == S::EMPTY {
-                conflict += j_mul_k;
+                conflict += j.1.mass() * k.1.mass();
             } else {
-                map.insert(j_cap_k, j_mul_k);
+                map.insert(j_cap_k, T::meet(&j.0, &j.1, &k.0, &k.1))?;
             }
         }
 
+        if conflict >= 1f32 {
+            return Err(CombError::Conflict);
+        }
+
         map.scale(1f32 / (1f32 - conflict));
-        map.consume()
+        Ok(map.consume())
+    }
+}
+
+/// The Proportional Conflict Redistribution rule no. 5 (Smarandache, Dezert): each
+/// partial conflict `m_1(X) m_2(Y)`, where `X \cap Y = \emptyset`, is returned to `X`
+/// and `Y` in proportion to `m_1(X)` and `m_2(Y)`.
+///
+/// Only the lattice operations are needed, so this applies to `HyperPowerSet` where,
+/// under the free DSm model, there's never any conflict to redistribute. Beyond the
+/// `N * M` intersections, up to `N + M` of the inputs' focal sets may receive mass;
+/// should that exceed the accumulator, the `OverflowPolicy` decides.
+pub struct PCR5<O = Reject>(PhantomData<O>);
+
+impl<S, T, O> CombRule<S, T> for PCR5<O>
+where
+    S: Set + Hash + Eq + Clone,
+    T: Mass + Clone,
+    O: OverflowPolicy<S, T>,
+{
+    fn comb<const N: usize, const M: usize>(
+        a: &[Option<(S, T)>; N],
+        b: &[Option<(S, T)>; M],
+    ) -> Result<impl Iterator<Item = (S, T)>, CombError> {
+        let mut map: SummationHM<N, M, S, T> = SummationHM::default();
+
+        for (j, k) in a
+            .iter()
+            .flatten()
+            .flat_map(|j| b.iter().flatten().map(move |k| (j, k)))
+        {
+            let j_cap_k = S::cap(&j.0, &k.0);
+            let (m_j, m_k) = (j.1.mass(), k.1.mass());
+
+            if j_cap_k != S::EMPTY {
+                map.insert_or(j_cap_k, T::meet(&j.0, &j.1, &k.0, &k.1), O::absorb)?;
+            } else if m_j * m_k > 0f32 {
+                // m_1(X)^2 m_2(Y) / (m_1(X) + m_2(Y)) and vice-versa; each side's share
+                // is scaled from its own mass.
+                let (mut x, mut y) = (j.1.clone(), k.1.clone());
+                x *= m_j * m_k / (m_j + m_k);
+                y *= m_j * m_k / (m_j + m_k);
+                map.insert_or(j.0.clone(), x, O::absorb)?;
+                map.insert_or(k.0.clone(), y, O::absorb)?;
+            }
+        }
+
+        Ok(map.consume())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set::HyperPowerSet;
+
+    const TOL: f32 = 0.001;
+
+    fn mass<S: Set + Hash + Eq + Clone, const N: usize>(
+        a: &[Option<(S, f32)>; N],
+        b: &[Option<(S, f32)>; N],
+        q: &S,
+    ) -> f32 {
+        <PCR5>::comb(a, b)
+            .unwrap()
+            .filter(|(p, _)| p == q)
+            .map(|(_, m)| m)
+            .sum()
+    }
+
+    #[test]
+    fn test_dempster_total_conflict() {
+        let m1 = [Some((0b01usize, 1f32))];
+        let m2 = [Some((0b10usize, 1f32))];
+        assert_eq!(Dempster::comb(&m1, &m2).err(), Some(CombError::Conflict));
+    }
+
+    #[test]
+    fn test_pcr5() {
+        const A: usize = 0b01;
+        const B: usize = 0b10;
+        let m1 = [Some((A, 0.6f32)), Some((B, 0.3f32)), Some((A | B, 0.1f32))];
+        let m2 = [Some((A, 0.2f32)), Some((B, 0.3f32)), Some((A | B, 0.5f32))];
+
+        // Conjunctive: A = 0.44, B = 0.27, A | B = 0.05; then the conflicts
+        // 0.6 * 0.3 and 0.3 * 0.2 are returned proportionally.
+        assert!((mass(&m1, &m2, &A) - 0.584f32).abs() < TOL);
+        assert!((mass(&m1, &m2, &B) - 0.366f32).abs() < TOL);
+        assert!((mass(&m1, &m2, &(A | B)) - 0.05f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_pcr5_free_dsm() {
+        type D2 = HyperPowerSet<2>;
+        let (a, b) = (D2::theta(0), D2::theta(1));
+        let m1 = [Some((a, 0.8f32)), Some((D2::total(), 0.2f32))];
+        let m2 = [Some((b, 0.9f32)), Some((D2::total(), 0.1f32))];
+
+        // No conflict: the overlap of the hypotheses is a focal element in its own right.
+        let a_cap_b = D2::cap(&a, &b);
+        assert!((mass(&m1, &m2, &a_cap_b) - 0.72f32).abs() < TOL);
+        assert!((mass(&m1, &m2, &a) - 0.08f32).abs() < TOL);
+        assert!((mass(&m1, &m2, &b) - 0.18f32).abs() < TOL);
+        assert!((mass(&m1, &m2, &D2::total()) - 0.02f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_pcr5_overflow() {
+        const A: usize = 0b00001;
+        const B: usize = 0b00010;
+        const C: usize = 0b00100;
+        const D: usize = 0b01000;
+        const E: usize = 0b10000;
+        // The intersections `A` and `C`, alongside `A | B`, `C | D` and `E` from the
+        // conflicts, are one more than the four slots.
+        let m1 = [Some((A | B, 0.4f32)), Some((C | D, 0.6f32))];
+        let m2 = [Some((A | C, 0.5f32)), Some((E, 0.5f32))];
+
+        assert_eq!(<PCR5>::comb(&m1, &m2).err(), Some(CombError::Overflow));
+
+        let merged: [Option<(usize, f32)>; 4] = {
+            let mut iter = PCR5::<MergeSmallest>::comb(&m1, &m2).unwrap();
+            core::array::from_fn(|_| iter.next())
+        };
+        let united: [Option<(usize, f32)>; 4] = {
+            let mut iter = PCR5::<Union>::comb(&m1, &m2).unwrap();
+            core::array::from_fn(|_| iter.next())
+        };
+        for bba in [merged, united] {
+            let total: f32 = bba.iter().flatten().map(|x| x.1).sum();
+            assert!((total - 1f32).abs() < TOL);
+        }
+
+        // Under `Union`, the focal sets still cover everything that received mass.
+        let cover = united.iter().flatten().fold(0usize, |acc, x| acc | x.0);
+        assert_eq!(cover, A | B | C | D | E);
     }
 }
@@ -1,54 +1,189 @@
 //! Containers needed for our Dempster-Shafer impls.
 
-/// "Exhaustive Map" -- a map where we find keys via exhaustive search.
-pub(super) mod em {
+/// "Hashed Map" -- a map where keys are placed via hashing and linear probing.
+pub(super) mod hm {
+    use crate::approx::Mass;
+    use core::cmp::Ordering;
+    use core::hash::{Hash, Hasher};
     use core::ops::{AddAssign, MulAssign};
 
-    /// A map that when `insert` is called the values are summed; keys are found via
-    /// exhaustive search.
-    pub struct SummationEM<const N: usize, K, V> {
-        // TODO: We need `generic_const_expr` to compute `N * N`.
-        // buf: [Option<(K, V)>; N * N],
-        // ... but a [[...; N]; N] dimension arr is potentially a way to avoid this.
-        buf: [[Option<(K, V)>; N]; N],
+    /// Returned when a key can't be placed because the map is full.
+    #[derive(Debug, PartialEq)]
+    pub struct Overflow;
+
+    /// FNV-1a; `core` doesn't ship a `Hasher`, and we don't need anything stronger
+    /// since the keys aren't adversarial.
+    struct Fnv(u64);
+
+    impl Default for Fnv {
+        fn default() -> Self {
+            Self(0xcbf2_9ce4_8422_2325)
+        }
     }
 
-    impl<const N: usize, K, V> Default for SummationEM<N, K, V> {
+    impl Hasher for Fnv {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for b in bytes {
+                self.0 ^= *b as u64;
+                self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    /// A map that when `insert` is called the values are summed; keys are placed via
+    /// hashing with linear probing, so an `insert` is `O(1)` rather than `O(N * N)`.
+    pub struct SummationHM<const R: usize, const C: usize, K, V> {
+        // TODO: We need `generic_const_expr` to compute `R * C`.
+        // buf: [Option<(K, V)>; R * C],
+        // ... but a [[...; C]; R] dimension arr is potentially a way to avoid this.
+        buf: [[Option<(K, V)>; C]; R],
+    }
+
+    impl<const R: usize, const C: usize, K, V> Default for SummationHM<R, C, K, V> {
         fn default() -> Self {
-            // TODO: If `(K, V)` is `Copy` this becomes `[[None; N]; N]` which is much,
+            // TODO: If `(K, V)` is `Copy` this becomes `[[None; C]; R]` which is much,
             // much nicer. There's also opportunities for `MaybeUninit`? This buffer should
             // just be zeroed (assuming that stable Rust zeroes `None`).
-            let buf: [[Option<(K, V)>; N]; N] =
+            let buf: [[Option<(K, V)>; C]; R] =
                 core::array::from_fn(|_| core::array::from_fn(|_| None));
             Self { buf }
         }
     }
 
-    impl<const N: usize, K, V> SummationEM<N, K, V>
+    impl<const R: usize, const C: usize, K, V> SummationHM<R, C, K, V>
     where
-        K: PartialEq,
-        V: AddAssign + MulAssign,
+        K: Hash + Eq,
+        V: AddAssign,
     {
+        /// Compute the slot that probing for `k` starts from.
+        fn home(k: &K, len: usize) -> usize {
+            let mut hasher = Fnv::default();
+            k.hash(&mut hasher);
+            (hasher.finish() % len as u64) as usize
+        }
+
         /// Insert a `(K, V)` pair into the map, summing the `V` value if found.
-        pub fn insert(&mut self, k: K, v: V) {
-            let mem = self
-                .buf
-                .iter_mut()
-                .flatten()
-                // We're looking for a `None` or the index where the keys match, so DeMorgan's...
-                .find(|z| !z.as_ref().is_some_and(|y| y.0 != k))
-                .expect("Should have found an index to place this KV in; is N correct?.");
+        pub fn insert(&mut self, k: K, v: V) -> Result<(), Overflow> {
+            let buf = self.buf.as_flattened_mut();
+            if buf.is_empty() {
+                return Err(Overflow);
+            }
+
+            let start = Self::home(&k, buf.len());
+
+            // Probe from `start`, wrapping around, for either the key or a `None`; since
+            // nothing is ever removed, hitting a `None` means the key isn't present.
+            let len = buf.len();
+            let mem = (0..len)
+                .map(|i| (start + i) % len)
+                .find(|i| !buf[*i].as_ref().is_some_and(|y| y.0 != k))
+                .map(|i| &mut buf[i])
+                .ok_or(Overflow)?;
 
             if let Some(inner) = mem {
                 inner.1 += v;
             } else {
                 mem.replace((k, v));
             }
+
+            Ok(())
         }
 
-        pub fn scale(&mut self, s: V)
+        /// Insert a `(K, V)` pair into the map; should it be full, the entry with the
+        /// least mass is taken out and `absorb` folds the pair into it before it's put
+        /// back. Should `absorb` fail, the entry is put back untouched.
+        pub fn insert_or<E>(
+            &mut self,
+            k: K,
+            v: V,
+            absorb: impl FnOnce(&mut (K, V), (K, V)) -> Result<(), E>,
+        ) -> Result<(), E>
         where
-            V: Copy, // See the below TODO and the commit info.
+            V: Mass,
+            E: From<Overflow>,
+        {
+            self.insert_or_by_key(k, v, |x| x.1.mass(), absorb)
+        }
+
+        /// As `insert_or`, though the entry taken out is the least by `key` rather
+        /// than by mass.
+        pub fn insert_or_by_key<E, O: PartialOrd>(
+            &mut self,
+            k: K,
+            v: V,
+            key: impl Fn(&(K, V)) -> O,
+            absorb: impl FnOnce(&mut (K, V), (K, V)) -> Result<(), E>,
+        ) -> Result<(), E>
+        where
+            E: From<Overflow>,
+        {
+            // Check for room first; a failed `insert` would drop the pair.
+            let buf = self.buf.as_flattened();
+            let len = buf.len();
+            let start = if len == 0 {
+                return Err(Overflow.into());
+            } else {
+                Self::home(&k, len)
+            };
+            let fits = (0..len)
+                .map(|i| &buf[(start + i) % len])
+                .any(|x| x.as_ref().is_none_or(|y| y.0 == k));
+            if fits {
+                return Ok(self.insert(k, v)?);
+            }
+
+            let smallest = (0..len)
+                .min_by(|i, j| {
+                    let key = |x: usize| key(buf[x].as_ref().unwrap());
+                    key(*i).partial_cmp(&key(*j)).unwrap_or(Ordering::Equal)
+                })
+                .unwrap();
+            let mut entry = self.remove(smallest);
+            let absorbed = absorb(&mut entry, (k, v));
+
+            // A slot was just freed, so this can't overflow.
+            self.insert(entry.0, entry.1)?;
+            absorbed
+        }
+
+        /// Take the entry at slot `idx` out of the map, shifting back any entries
+        /// whose probe passed over it so that they remain reachable.
+        fn remove(&mut self, idx: usize) -> (K, V) {
+            let buf = self.buf.as_flattened_mut();
+            let len = buf.len();
+            let removed = buf[idx].take().unwrap();
+
+            let (mut hole, mut j) = (idx, idx);
+            loop {
+                j = (j + 1) % len;
+                let Some((k, _)) = &buf[j] else {
+                    break;
+                };
+
+                // The entry stays put should its home lie cyclically within (hole, j].
+                let h = Self::home(k, len);
+                let stays = if hole <= j {
+                    hole < h && h <= j
+                } else {
+                    hole < h || h <= j
+                };
+                if !stays {
+                    buf[hole] = buf[j].take();
+                    hole = j;
+                }
+            }
+
+            removed
+        }
+
+        pub fn scale<F>(&mut self, s: F)
+        where
+            F: Copy, // See the below TODO and the commit info.
+            V: MulAssign<F>,
         {
             self.buf
                 .iter_mut()
@@ -63,7 +198,7 @@ pub(super) mod em {
         // TODO: Think about `IntoIter` rather than this?
         /// Return an iterator over the underlying buffer.
         pub fn consume(self) -> impl Iterator<Item = (K, V)> {
-            // [[...; N]; N] -> [...; N * N] alongside dumping all `None` options.
+            // [[...; C]; R] -> [...; R * C] alongside dumping all `None` options.
             self.buf.into_iter().flatten().flatten()
         }
     }
@@ -74,15 +209,94 @@ pub(super) mod em {
 
         #[test]
         fn test_insert() {
-            let mut shm = SummationEM::<3, usize, usize>::default();
-            shm.insert(0, 10);
-            shm.insert(1, 20);
-            shm.insert(0, 30);
+            let mut shm = SummationHM::<3, 3, usize, usize>::default();
+            shm.insert(0, 10).unwrap();
+            shm.insert(1, 20).unwrap();
+            shm.insert(0, 30).unwrap();
 
+            let mut kv = [(0, 0); 2];
             let mut iter = shm.consume();
-            assert_eq!(iter.next(), Some((0, 40)));
-            assert_eq!(iter.next(), Some((1, 20)));
+            kv.iter_mut().for_each(|x| *x = iter.next().unwrap());
             assert!(iter.next().is_none());
+
+            // Placement is by hash, so the order isn't fixed.
+            kv.sort();
+            assert_eq!(kv, [(0, 40), (1, 20)]);
+        }
+
+        #[test]
+        fn test_insert_full() {
+            let mut shm = SummationHM::<2, 2, usize, usize>::default();
+            (0..4).for_each(|k| shm.insert(k, 1).unwrap());
+
+            // Every slot is taken, but summing into an existing key is fine.
+            assert_eq!(shm.insert(2, 1), Ok(()));
+            assert_eq!(shm.insert(4, 1), Err(Overflow));
+            assert_eq!(shm.consume().map(|x| x.1).sum::<usize>(), 5);
+        }
+
+        #[test]
+        fn test_insert_or() {
+            let mut shm = SummationHM::<2, 2, usize, f32>::default();
+            (0..4).for_each(|k| shm.insert(k, 1f32 + k as f32).unwrap());
+
+            // The smallest entry (0, 1.0) absorbs the pair under a new key.
+            let absorb = |e: &mut (usize, f32), x: (usize, f32)| -> Result<(), Overflow> {
+                e.0 |= x.0;
+                e.1 += x.1;
+                Ok(())
+            };
+            shm.insert_or(8, 0.5f32, absorb).unwrap();
+            assert_eq!(shm.insert_or(1, 1f32, absorb), Ok(())); // Room to sum.
+
+            let mut kv = [(0, 0f32); 4];
+            let mut iter = shm.consume();
+            kv.iter_mut().for_each(|x| *x = iter.next().unwrap());
+            kv.sort_by_key(|x| x.0);
+            assert_eq!(kv, [(1, 3f32), (2, 3f32), (3, 4f32), (8, 1.5f32)]);
+
+            // Rejecting leaves the map untouched.
+            let mut shm = SummationHM::<1, 1, usize, f32>::default();
+            shm.insert(0, 1f32).unwrap();
+            let reject = |_: &mut (usize, f32), _| Err(Overflow);
+            assert_eq!(shm.insert_or(1, 2f32, reject), Err(Overflow));
+            assert_eq!(shm.consume().next(), Some((0, 1f32)));
+        }
+
+        #[test]
+        fn test_insert_or_by_key() {
+            let mut shm = SummationHM::<1, 3, usize, f32>::default();
+            [(0b001, 0.1f32), (0b110, 0.2), (0b011, 0.7)]
+                .into_iter()
+                .for_each(|(k, v)| shm.insert(k, v).unwrap());
+
+            // Prefer the least entry that shares a bit with the pair.
+            let key = |x: &(usize, f32)| (x.0 & 0b100 == 0, x.1);
+            let absorb = |e: &mut (usize, f32), x: (usize, f32)| -> Result<(), Overflow> {
+                e.1 += x.1;
+                Ok(())
+            };
+            shm.insert_or_by_key(0b100, 0.5f32, key, absorb).unwrap();
+
+            let mut kv = [(0, 0f32); 3];
+            let mut iter = shm.consume();
+            kv.iter_mut().for_each(|x| *x = iter.next().unwrap());
+            kv.sort_by_key(|x| x.0);
+            assert_eq!(kv, [(0b001, 0.1f32), (0b011, 0.7f32), (0b110, 0.7f32)]);
+        }
+
+        #[test]
+        fn test_remove() {
+            // In a full map the probe chains run into one another; removal must keep
+            // the rest reachable, else re-inserting them would overflow.
+            let mut shm = SummationHM::<3, 3, usize, usize>::default();
+            (0..9).for_each(|k| shm.insert(k, 1).unwrap());
+            for _ in 0..4 {
+                let occupied = shm.buf.as_flattened().iter().position(|x| x.is_some());
+                shm.remove(occupied.unwrap());
+            }
+            (0..9).for_each(|k| shm.insert(k, 1).unwrap());
+            assert_eq!(shm.consume().map(|x| x.1).sum::<usize>(), 9 + 5);
         }
     }
 }
@@ -90,85 +304,124 @@ pub(super) mod em {
 /// Heap-oriented structures.
 pub mod heap {
 
-    /// A Bounded Priority Heap.
+    /// A Bounded Priority Heap; a min-heap that retains the `N` largest values.
     #[derive(Debug)]
     pub struct PriorityHeap<const N: usize, T> {
         buf: [Option<T>; N],
+        len: usize,
     }
 
     impl<const N: usize, T> Default for PriorityHeap<N, T> {
         fn default() -> Self {
             Self {
                 buf: core::array::from_fn(|_| None),
+                len: 0usize,
             }
         }
     }
 
     impl<const N: usize, T> PriorityHeap<N, T> {
-        /// The index at which leaves begin.
-        const LEAF_IDX: usize = usize::ilog2(N) as usize;
-
         /// Compute the parent of a child with the array representation of a heap.
         const fn parent(x: usize) -> usize {
             assert!(x != 0); // We should never call this function on the root.
-            match x % 2 {
-                0 => (x - 2) / 2,
-                1 => (x - 1) / 2,
-                _ => unreachable!(),
-            }
+            (x - 1) / 2
+        }
+
+        /// Compute the left child of a parent; the right child is the next index.
+        const fn left(x: usize) -> usize {
+            2 * x + 1
         }
 
-        /// Assert that the heap condition holds for the child `idx`.
-        fn heap_upkeep<R: PartialOrd>(&mut self, f: impl Fn(&T) -> R, child_idx: usize) {
-            // If the `child_idx` is the root, then the heap condition holds and we exit.
-            if child_idx == 0 {
-                return;
+        /// Fetch the key of the occupied index `idx`.
+        fn key<R>(&self, f: &impl Fn(&T) -> R, idx: usize) -> R {
+            f(self.buf[idx].as_ref().unwrap())
+        }
+
+        /// Move the value at `idx` towards the root until the heap condition holds.
+        fn sift_up<R: PartialOrd>(&mut self, f: &impl Fn(&T) -> R, mut idx: usize) {
+            while idx != 0 {
+                let parent_idx = Self::parent(idx);
+                // If the child is smaller than the parent, we need to swap them.
+                if self.key(f, idx) < self.key(f, parent_idx) {
+                    self.buf.swap(parent_idx, idx);
+                    idx = parent_idx;
+                } else {
+                    break;
+                }
             }
+        }
 
-            let parent_idx = Self::parent(child_idx);
+        /// Move the value at `idx` towards the leaves until the heap condition holds.
+        fn sift_down<R: PartialOrd>(&mut self, f: &impl Fn(&T) -> R, mut idx: usize) {
+            loop {
+                let (l, r) = (Self::left(idx), Self::left(idx) + 1);
+                let mut min = idx;
+                if l < self.len && self.key(f, l) < self.key(f, min) {
+                    min = l;
+                }
+                if r < self.len && self.key(f, r) < self.key(f, min) {
+                    min = r;
+                }
 
-            let p = self.buf.get(parent_idx).unwrap();
-            let c = self.buf.get(child_idx).unwrap();
+                if min == idx {
+                    break;
+                }
+                self.buf.swap(min, idx);
+                idx = min;
+            }
+        }
 
-            // If the child is larger than the parent, we need to swap them.
-            if f(c.as_ref().unwrap()) > f(p.as_ref().unwrap()) {
-                self.buf.swap(parent_idx, child_idx);
-                // Assert that the condition holds for the *new* parent index.
-                self.heap_upkeep(f, parent_idx);
-            };
+        /// Return the smallest value within the Heap.
+        pub fn peek_min(&self) -> Option<&T> {
+            self.buf.first().and_then(|x| x.as_ref())
         }
 
         // TODO: `impl Fn(&T) -> R`: this constrains the API to things that can `Copy`.
-        /// Insert a value into the Heap, returning the value that was ejected.
+        /// Insert a value into the Heap, returning the value that was ejected; when
+        /// full, this is the smaller of `v` and the current minimum.
         pub fn insert_by_key<R: PartialOrd>(&mut self, f: impl Fn(&T) -> R, v: T) -> Option<T> {
-            // If there's a `None`, attempt to find it and replace it.
-            let (idx, r) = if let Some((idx, mem)) =
-                self.buf.iter_mut().enumerate().find(|(_, x)| x.is_none())
-            {
-                (idx, mem.replace(v))
+            if self.len < N {
+                // There's room, place it at the end and let it rise.
+                self.buf[self.len] = Some(v);
+                self.len += 1;
+                self.sift_up(&f, self.len - 1);
+                None
+            } else if self.peek_min().is_some_and(|min| f(min) < f(&v)) {
+                // Otherwise, `v` replaces the minimum if it's larger.
+                let r = self.buf[0].replace(v);
+                self.sift_down(&f, 0);
+                r
             } else {
-                // Otherwise, find the minimum value then replace it.
-                let (idx, mem) = self.buf[Self::LEAF_IDX..]
-                    .iter_mut()
-                    .enumerate()
-                    .reduce(|(a, min), (b, e)| {
-                        if f(min.as_ref().unwrap()) > f(e.as_ref().unwrap()) {
-                            (b, e)
-                        } else {
-                            (a, min)
-                        }
-                    })
-                    .unwrap();
-
-                (idx + Self::LEAF_IDX, mem.replace(v)) // We started at LEAF_IDX, add it back...
-            };
+                Some(v)
+            }
+        }
 
-            self.heap_upkeep(f, idx);
+        /// Remove and return the smallest value within the Heap.
+        pub fn pop_min_by_key<R: PartialOrd>(&mut self, f: impl Fn(&T) -> R) -> Option<T> {
+            if self.len == 0 {
+                return None;
+            }
+
+            self.len -= 1;
+            self.buf.swap(0, self.len);
+            let r = self.buf[self.len].take();
+            self.sift_down(&f, 0);
             r
         }
 
-        /// Return the underyling buffer.
-        pub fn consume(self) -> [Option<T>; N] {
+        /// Return the underlying values in descending order; any `None` trail.
+        pub fn consume_sorted_by_key<R: PartialOrd>(
+            mut self,
+            f: impl Fn(&T) -> R,
+        ) -> [Option<T>; N] {
+            // Heapsort: popping the minimum frees the slot at the end of the heap, which
+            // is exactly where it belongs in a descending order.
+            while self.len > 1 {
+                let end = self.len - 1;
+                let min = self.pop_min_by_key(&f);
+                self.buf[end] = min;
+            }
+
             self.buf
         }
     }
@@ -202,9 +455,43 @@ pub mod heap {
             // The Heap should contain 8, 7, ... 8 - N; if they sum equivalently we've
             // capture them all
             assert_eq!(
-                ph.consume().iter().flatten().sum::<usize>(),
+                ph.consume_sorted_by_key(f).iter().flatten().sum::<usize>(),
                 ((8 - N)..8).sum()
             );
         }
+
+        #[test]
+        fn test_pq_ejected() {
+            let mut ph = PH::default();
+            let f = |x: &usize| *x;
+
+            [5, 3, 9, 1].into_iter().for_each(|x| {
+                assert!(ph.insert_by_key(f, x).is_none());
+            });
+
+            assert_eq!(ph.insert_by_key(f, 0), Some(0)); // Smaller than everything.
+            assert_eq!(ph.insert_by_key(f, 4), Some(1)); // Evicts the minimum.
+            assert_eq!(ph.peek_min(), Some(&3));
+        }
+
+        #[test]
+        fn test_pq_sorted() {
+            let mut ph = PH::default();
+            let f = |x: &usize| *x;
+
+            [2, 7, 1, 8, 2, 8, 1, 8].into_iter().for_each(|x| {
+                ph.insert_by_key(f, x);
+            });
+            assert_eq!(ph.consume_sorted_by_key(f), [8, 8, 8, 7].map(Some));
+
+            let mut ph = PH::default();
+            [3, 1, 2].into_iter().for_each(|x| {
+                ph.insert_by_key(f, x);
+            });
+            assert_eq!(
+                ph.consume_sorted_by_key(f),
+                [Some(3), Some(2), Some(1), None]
+            );
+        }
     }
 }
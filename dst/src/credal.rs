@@ -0,0 +1,182 @@
+//! Credal sets: the probabilities bounded below by a capacity.
+//!
+//! A capacity over a frame of `n` singletons is dense, as in `dense`: `[f32; L]` with
+//! `L = 2^n`, where index `i` is the subset whose bitmask is `i`. A BBA's belief
+//! function is such a capacity, and it's the lower probability of its credal set
+//! `\{P : P(A) \geq bel(A)\}`. Conversely, a capacity is a belief function exactly
+//! when it's infinitely monotone, i.e. its Möbius inverse is non-negative; the weaker
+//! 2-monotone capacities (e.g. from interval probabilities) still have a credal set
+//! whose vertices follow from the orderings of the frame.
+use crate::dense::{self, width};
+use crate::set::Bitset;
+
+/// Compute the belief function of a BBA over the frame of singletons `0..n`, i.e.
+/// the lower probability of its credal set.
+pub fn belief<const L: usize, S: Bitset>(bba: impl IntoIterator<Item = (S, f32)>) -> [f32; L] {
+    let mut bel: [f32; L] = dense::from_sparse(bba);
+    for bit in (0..width::<L>()).map(|i| 1usize << i) {
+        for a in (0..L).filter(|a| a & bit != 0) {
+            bel[a] += bel[a ^ bit];
+        }
+    }
+    bel
+}
+
+/// Compute the Möbius inverse of a capacity; this is its BBA should it be infinitely
+/// monotone (see `is_infinitely_monotone`).
+pub fn mobius<const L: usize>(nu: &[f32; L]) -> [f32; L] {
+    let mut m = *nu;
+    for bit in (0..width::<L>()).map(|i| 1usize << i) {
+        for a in (0..L).filter(|a| a & bit != 0) {
+            m[a] -= m[a ^ bit];
+        }
+    }
+    m
+}
+
+/// Compute the lower probability of the event `a`.
+pub fn lower<const L: usize>(nu: &[f32; L], a: usize) -> f32 {
+    nu[a & (L - 1)]
+}
+
+/// Compute the upper probability of the event `a`, `1 - \nu(\neg A)`.
+pub fn upper<const L: usize>(nu: &[f32; L], a: usize) -> f32 {
+    1f32 - nu[!a & (L - 1)]
+}
+
+/// The lower probability of the interval probabilities `lo_i \leq P(\{\theta_i\}) \leq
+/// hi_i` over `K` singletons: `\nu(A) = \max(\sum_{A} lo_i, 1 - \sum_{\neg A} hi_i)`.
+/// The intervals should be reachable, i.e. each bound is attained by some probability.
+pub fn from_intervals<const K: usize, const L: usize>(lo: &[f32; K], hi: &[f32; K]) -> [f32; L] {
+    assert_eq!(width::<L>(), K);
+    assert!(lo
+        .iter()
+        .zip(hi)
+        .all(|(l, h)| 0f32 <= *l && l <= h && *h <= 1f32));
+    assert!(lo.iter().sum::<f32>() <= 1f32 && hi.iter().sum::<f32>() >= 1f32);
+
+    core::array::from_fn(|a| {
+        let (inside, outside) = (0..K).fold((0f32, 0f32), |(i, o), k| {
+            if a & 1 << k != 0 {
+                (i + lo[k], o)
+            } else {
+                (i, o + hi[k])
+            }
+        });
+        f32::max(inside, 1f32 - outside)
+    })
+}
+
+/// Compute whether a capacity is 2-monotone (supermodular):
+/// `\nu(A \cup B) + \nu(A \cap B) \geq \nu(A) + \nu(B)`. It's enough to check each `A`
+/// alongside two singletons `\theta_i, \theta_j \notin A`.
+pub fn is_two_monotone<const L: usize>(nu: &[f32; L], tol: f32) -> bool {
+    let n = width::<L>();
+    (0..L).all(|a| {
+        (0..n).filter(|i| a & 1 << i == 0).all(|i| {
+            (i + 1..n).filter(|j| a & 1 << j == 0).all(|j| {
+                let (ai, aj) = (a | 1 << i, a | 1 << j);
+                nu[ai | aj] + nu[a] - nu[ai] - nu[aj] >= -tol
+            })
+        })
+    })
+}
+
+/// Compute whether a capacity is infinitely monotone, i.e. a belief function: its
+/// Möbius inverse is non-negative and it's normalized.
+pub fn is_infinitely_monotone<const L: usize>(nu: &[f32; L], tol: f32) -> bool {
+    nu[0].abs() <= tol && (nu[L - 1] - 1f32).abs() <= tol && mobius(nu).iter().all(|m| *m >= -tol)
+}
+
+/// Produce the vertices of the credal set of a 2-monotone capacity over `K` singletons:
+/// for each of the `K!` orderings `\sigma` of the frame,
+/// `P(\sigma_k) = \nu(\{\sigma_1, ..., \sigma_k\}) - \nu(\{\sigma_1, ..., \sigma_{k-1}\})`.
+/// Distinct orderings may produce the same vertex.
+pub fn vertices<const K: usize, const L: usize>(
+    nu: &[f32; L],
+) -> impl Iterator<Item = [f32; K]> + '_ {
+    assert_eq!(width::<L>(), K);
+    let count: usize = (1..=K).product();
+
+    (0..count).map(move |idx| {
+        // Decode the ordering from its index in the factorial number system.
+        let mut remaining: [usize; K] = core::array::from_fn(|i| i);
+        let (mut idx, mut len) = (idx, K);
+        let (mut p, mut prefix) = ([0f32; K], 0usize);
+        while len > 0 {
+            let f: usize = (1..len).product();
+            let k = remaining[idx / f];
+            remaining.copy_within(idx / f + 1..len, idx / f);
+            (idx, len) = (idx % f, len - 1);
+
+            p[k] = nu[prefix | 1 << k] - nu[prefix];
+            prefix |= 1 << k;
+        }
+        p
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dst::tests::traffic_light::*;
+    use crate::dst::{bel, pl};
+
+    const TOL: f32 = 0.001;
+
+    #[test]
+    fn test_belief_round_trip() {
+        let nu: [f32; 8] = belief(TRAFFIC_BBA.iter().copied());
+        for a in 0..8usize {
+            assert!((lower(&nu, a) - bel(TRAFFIC_BBA, &a)).abs() < TOL);
+            assert!((upper(&nu, a) - pl(TRAFFIC_BBA, &a)).abs() < TOL);
+        }
+
+        assert!(is_infinitely_monotone(&nu, TOL));
+        assert!(is_two_monotone(&nu, TOL));
+        let m = mobius(&nu);
+        for &(s, v) in TRAFFIC_BBA {
+            assert!((m[s] - v).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_vertices() {
+        let nu: [f32; 8] = belief(TRAFFIC_BBA.iter().copied());
+        let mut count = 0;
+        for p in vertices::<3, 8>(&nu) {
+            assert!((p.iter().sum::<f32>() - 1f32).abs() < TOL);
+            for a in 0..8usize {
+                let p_a: f32 = (0..3).filter(|i| a & 1 << i != 0).map(|i| p[i]).sum();
+                assert!(p_a >= lower(&nu, a) - TOL && p_a <= upper(&nu, a) + TOL);
+            }
+            count += 1;
+        }
+        assert_eq!(count, 6);
+
+        // Ordering red last gives it all it can have: \max P(red) = pl(red).
+        let last_red = vertices::<3, 8>(&nu).map(|p| p[2]).fold(0f32, f32::max);
+        assert!((last_red - pl(TRAFFIC_BBA, &RED)).abs() < TOL);
+    }
+
+    #[test]
+    fn test_intervals() {
+        // 2-monotone, but not a belief function: m(\Theta) = -0.2.
+        let nu: [f32; 8] = from_intervals(&[0.1, 0.1, 0.1], &[0.5, 0.5, 0.5]);
+        assert!((lower(&nu, 0b011) - 0.5f32).abs() < TOL);
+        assert!((upper(&nu, 0b001) - 0.5f32).abs() < TOL);
+        assert!(is_two_monotone(&nu, TOL));
+        assert!(!is_infinitely_monotone(&nu, TOL));
+
+        // Precise intervals are a probability, and so a (Bayesian) belief function.
+        let precise: [f32; 8] = from_intervals(&[0.2, 0.3, 0.5], &[0.2, 0.3, 0.5]);
+        assert!(is_infinitely_monotone(&precise, TOL));
+    }
+
+    #[test]
+    fn test_not_two_monotone() {
+        let nu = [0f32, 0.6, 0.6, 1.0];
+        assert!(!is_two_monotone(&nu, TOL));
+        assert!(!is_infinitely_monotone(&nu, TOL));
+    }
+}
@@ -0,0 +1,173 @@
+//! An exact, dense representation of BBAs over small bitset frames.
+//!
+//! A BBA over a frame of `n` singletons is `[f32; L]` with `L = 2^n`, where index `i`
+//! is the subset whose bitmask is `i`. Combination goes through the commonality
+//! function `q(A) = \sum_{B \supseteq A} m(B)`: the conjunctive rule is the pointwise
+//! product of the commonalities, and each transform is the fast (superset) Möbius
+//! transform, so combining is `O(n 2^n)` with no approximation.
+//!
+//! TODO: We need `generic_const_expr` to write `[f32; 1 << n]`; instead `L` is checked
+//! to be a power of two.
+use crate::comb::CombError;
+use crate::set::Bitset;
+
+/// Compute `n` for a dense BBA of length `L`.
+pub(crate) const fn width<const L: usize>() -> usize {
+    assert!(L.is_power_of_two(), "Should have a length of `2^n`.");
+    L.trailing_zeros() as usize
+}
+
+/// Compute the index of a subset of the frame `0..n`.
+fn index<S: Bitset>(s: &S, n: usize) -> usize {
+    (0..n)
+        .filter(|i| S::singleton(*i).is_subset(s))
+        .fold(0usize, |acc, i| acc | 1 << i)
+}
+
+/// Compute the subset at an index.
+fn subset<S: Bitset>(idx: usize, n: usize) -> S {
+    (0..n)
+        .filter(|i| idx & 1 << i != 0)
+        .fold(S::EMPTY, |acc, i| S::cup(&acc, &S::singleton(i)))
+}
+
+/// Build a dense BBA from a sparse one over the frame of singletons `0..n`; repeated
+/// focal sets are summed. Each focal set should lie within the frame.
+pub fn from_sparse<const L: usize, S: Bitset>(bba: impl IntoIterator<Item = (S, f32)>) -> [f32; L] {
+    let n = width::<L>();
+    let mut m = [0f32; L];
+    for (s, v) in bba {
+        let i = index(&s, n);
+        assert!(
+            s.is_subset(&subset::<S>(i, n)),
+            "Focal sets should lie within the frame."
+        );
+        m[i] += v;
+    }
+    m
+}
+
+/// Produce the focal sets of a dense BBA, e.g. for an `Approximation`.
+pub fn to_sparse<const L: usize, S: Bitset>(m: &[f32; L]) -> impl Iterator<Item = (S, f32)> + '_ {
+    let n = width::<L>();
+    m.iter()
+        .enumerate()
+        .filter(|(_, v)| **v != 0f32)
+        .map(move |(i, v)| (subset(i, n), *v))
+}
+
+/// Compute the commonality function of a dense BBA.
+pub fn commonality<const L: usize>(m: &[f32; L]) -> [f32; L] {
+    let mut q = *m;
+    for bit in (0..width::<L>()).map(|i| 1usize << i) {
+        for a in (0..L).filter(|a| a & bit == 0) {
+            q[a] += q[a | bit];
+        }
+    }
+    q
+}
+
+/// Recover a dense BBA from its commonality function.
+pub fn from_commonality<const L: usize>(q: &[f32; L]) -> [f32; L] {
+    let mut m = *q;
+    for bit in (0..width::<L>()).map(|i| 1usize << i) {
+        for a in (0..L).filter(|a| a & bit == 0) {
+            m[a] -= m[a | bit];
+        }
+    }
+    m
+}
+
+/// Smets' (unnormalized) conjunctive rule; the conflict is left upon `\emptyset`.
+pub fn conjunctive<const L: usize>(a: &[f32; L], b: &[f32; L]) -> [f32; L] {
+    let (qa, qb) = (commonality(a), commonality(b));
+    from_commonality(&core::array::from_fn(|i| qa[i] * qb[i]))
+}
+
+/// Dempster's rule: the conjunctive rule, normalized by the conflict.
+pub fn dempster<const L: usize>(a: &[f32; L], b: &[f32; L]) -> Result<[f32; L], CombError> {
+    let mut m = conjunctive(a, b);
+    let conflict = core::mem::take(&mut m[0]);
+    if conflict >= 1f32 {
+        return Err(CombError::Conflict);
+    }
+
+    m.iter_mut().for_each(|x| *x /= 1f32 - conflict);
+    Ok(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::KX;
+    use crate::comb::Dempster;
+    use crate::dst::tests::traffic_light::*;
+    use crate::dst::{bel, comb_approx, pl};
+
+    const TOL: f32 = 0.001;
+
+    const ALL: usize = RED | YELLOW | GREEN;
+
+    const A: [(usize, f32); 4] = [(RED, 0.35), (YELLOW, 0.25), (RED | YELLOW, 0.3), (ALL, 0.1)];
+    const B: [(usize, f32); 3] = [(RED | GREEN, 0.5), (YELLOW, 0.2), (ALL, 0.3)];
+
+    #[test]
+    fn test_commonality_round_trip() {
+        let m: [f32; 8] = from_sparse(A);
+        let q = commonality(&m);
+        assert!((q[RED] - (0.35f32 + 0.3 + 0.1)).abs() < TOL);
+        assert!((q[0] - 1f32).abs() < TOL);
+        for (l, r) in from_commonality(&q).iter().zip(m) {
+            assert!((l - r).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_dense_dempster() {
+        let m = dempster::<8>(&from_sparse(A), &from_sparse(B)).unwrap();
+        let sparse: [(usize, f32); 8] = {
+            let mut iter = to_sparse(&m);
+            core::array::from_fn(|_| iter.next().unwrap_or((0, 0f32)))
+        };
+        let exact = comb_approx::<16, usize, f32, KX, Dempster>([A.to_vec(), B.to_vec()]).unwrap();
+        for q in 0..8usize {
+            assert!((bel(&sparse, &q) - bel(&exact, &q)).abs() < TOL);
+            assert!((pl(&sparse, &q) - pl(&exact, &q)).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_dense_conjunctive() {
+        // The conflict remains upon the empty set.
+        let m = conjunctive::<4>(
+            &from_sparse([(0b01usize, 1f32)]),
+            &from_sparse([(0b10usize, 1f32)]),
+        );
+        assert_eq!(m, [1f32, 0f32, 0f32, 0f32]);
+        assert_eq!(
+            dempster::<4>(
+                &from_sparse([(0b01usize, 1f32)]),
+                &from_sparse([(0b10usize, 1f32)])
+            ),
+            Err(CombError::Conflict)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_outside_frame() {
+        // `{2}` isn't within the frame `{0, 1}`; it's not the empty set either.
+        let _: [f32; 4] = from_sparse([(0b100usize, 1f32)]);
+    }
+
+    #[test]
+    fn test_dense_large() {
+        // Twelve hypotheses; the dense form holds every one of the 4096 subsets.
+        const L: usize = 1 << 12;
+        let a: [f32; L] = from_sparse([(0b0000_0000_1111usize, 0.6f32), (L - 1, 0.4f32)]);
+        let b: [f32; L] = from_sparse([(0b0000_0011_1100usize, 0.7f32), (L - 1, 0.3f32)]);
+        let m = dempster(&a, &b).unwrap();
+        assert!((m[0b0000_0000_1100] - 0.42f32).abs() < TOL);
+        assert!((m.iter().sum::<f32>() - 1f32).abs() < TOL);
+    }
+}
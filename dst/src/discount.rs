@@ -0,0 +1,168 @@
+//! Shafer's discounting, and learning the discount rates from labelled data.
+//!
+//! A source that's reliable with probability `1 - \alpha` has its BBA discounted:
+//! `m^\alpha(A) = (1 - \alpha) m(A)` for `A \neq \Theta`, and
+//! `m^\alpha(\Theta) = (1 - \alpha) m(\Theta) + \alpha`.
+//!
+//! The rates are learned as in Elouedi, Mellouli and Smets, extended to the fused
+//! output as in Mercier et al.: minimize, over a history of BBAs alongside their true
+//! classes, the squared distance between the pignistic probabilities of the
+//! discounted, `Dempster` fused BBA and the indicator of the true class.
+//!
+//! Z. Elouedi, K. Mellouli, P. Smets, "Assessing sensor reliability for multisensor
+//! data fusion within the transferable belief model", IEEE Transactions on Systems,
+//! Man, and Cybernetics, 2004.
+//! D. Mercier, B. Quost, T. Denœux, "Refined modeling of sensor reliability in the
+//! belief function framework using contextual discounting", Information Fusion, 2008.
+use crate::approx::KX;
+use crate::comb::{CombError, Dempster};
+use crate::dst::{betp, comb_approx};
+use crate::set::Bitset;
+use core::hash::Hash;
+
+/// Discount a BBA at rate `alpha`, moving that fraction of the mass onto `frame`. The
+/// BBA should list `frame` among its focal sets, if with no mass, so that the result
+/// keeps its `M` elements.
+pub fn discount<const M: usize, S: Clone + Eq>(
+    bba: &[(S, f32); M],
+    alpha: f32,
+    frame: &S,
+) -> [(S, f32); M] {
+    assert!((0f32..=1f32).contains(&alpha));
+    assert!(
+        bba.iter().filter(|(s, _)| s == frame).count() == 1,
+        "The frame should appear once in the BBA."
+    );
+
+    bba.clone().map(|(s, m)| {
+        let m = (1f32 - alpha) * m;
+        match s == *frame {
+            true => (s, m + alpha),
+            false => (s, m),
+        }
+    })
+}
+
+/// A labelled observation: the BBA of each of the `P` sources alongside the index of
+/// the true singleton. Each BBA should list the frame; see `discount`.
+pub type Sample<const M: usize, const P: usize, S> = ([[(S, f32); M]; P], usize);
+
+/// Compute the total squared distance between the pignistic probabilities of the
+/// discounted, fused BBAs and the true classes, over the `K` singletons of the frame.
+pub fn loss<const N: usize, const M: usize, const P: usize, const K: usize, S>(
+    history: &[Sample<M, P, S>],
+    alpha: &[f32; P],
+    frame: &S,
+) -> Result<f32, CombError>
+where
+    S: Bitset + Hash + Eq + Clone,
+{
+    history.iter().try_fold(0f32, |acc, (bba, truth)| {
+        let fused = comb_approx::<N, S, f32, KX, Dempster>(
+            bba.iter().zip(alpha).map(|(m, a)| discount(m, *a, frame)),
+        )?;
+
+        Ok(acc
+            + (0..K)
+                .map(|i| {
+                    let d = betp(&fused, &S::singleton(i)) - (i == *truth) as u8 as f32;
+                    d * d
+                })
+                .sum::<f32>())
+    })
+}
+
+/// Learn a discount rate for each of the `P` sources by minimizing `loss`; each sweep
+/// runs a golden-section search over every rate in turn, holding the rest fixed. `N`
+/// bounds the fused BBA, as within `comb_approx`.
+pub fn learn<const N: usize, const M: usize, const P: usize, const K: usize, S>(
+    history: &[Sample<M, P, S>],
+    frame: &S,
+    sweeps: usize,
+) -> Result<[f32; P], CombError>
+where
+    S: Bitset + Hash + Eq + Clone,
+{
+    const STEPS: usize = 32;
+    let phi = (libm::sqrtf(5f32) - 1f32) / 2f32;
+
+    let mut alpha = [0.5f32; P];
+    let at = |alpha: &[f32; P], i: usize, x: f32| {
+        let mut alpha = *alpha;
+        alpha[i] = x;
+        loss::<N, M, P, K, S>(history, &alpha, frame)
+    };
+
+    for _ in 0..sweeps {
+        for i in 0..P {
+            let (mut lo, mut hi) = (0f32, 1f32);
+            let (mut c, mut d) = (hi - phi * (hi - lo), lo + phi * (hi - lo));
+            let (mut f_c, mut f_d) = (at(&alpha, i, c)?, at(&alpha, i, d)?);
+
+            for _ in 0..STEPS {
+                if f_c < f_d {
+                    (hi, d, f_d) = (d, c, f_c);
+                    c = hi - phi * (hi - lo);
+                    f_c = at(&alpha, i, c)?;
+                } else {
+                    (lo, c, f_c) = (c, d, f_d);
+                    d = lo + phi * (hi - lo);
+                    f_d = at(&alpha, i, d)?;
+                }
+            }
+
+            alpha[i] = (lo + hi) / 2f32;
+        }
+    }
+
+    Ok(alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dst::tests::abc::*;
+    use crate::dst::{bel, pl};
+
+    const TOL: f32 = 0.01;
+
+    #[test]
+    fn test_discount() {
+        let bba = [(A, 0.6f32), (A | B, 0.3f32), (FRAME, 0.1f32)];
+        let m = discount(&bba, 0.5, &FRAME);
+        assert_eq!(m.iter().filter(|(s, _)| *s == FRAME).count(), 1);
+        assert!((bel(&m, &A) - 0.3f32).abs() < TOL);
+        assert!((pl(&m, &B) - 0.7f32).abs() < TOL);
+        assert!((bel(&m, &FRAME) - 1f32).abs() < TOL);
+        assert!((m[2].1 - 0.55f32).abs() < TOL);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_discount_without_frame() {
+        discount(&[(A, 0.6f32), (A | B, 0.4f32)], 0.5, &FRAME);
+    }
+
+    #[test]
+    fn test_learn() {
+        // Source 0 is reliable; source 1 names the next class along.
+        let sample = |truth: usize, lie: usize| -> Sample<2, 2, usize> {
+            (
+                [
+                    [(1 << truth, 0.8f32), (FRAME, 0.2f32)],
+                    [(1 << lie, 0.8f32), (FRAME, 0.2f32)],
+                ],
+                truth,
+            )
+        };
+        let history = [sample(0, 1), sample(1, 2), sample(2, 0), sample(0, 1)];
+
+        let alpha = learn::<8, 2, 2, 3, usize>(&history, &FRAME, 3).unwrap();
+        assert!(alpha[0] < 0.1, "{alpha:?}");
+        assert!(alpha[1] > 0.9, "{alpha:?}");
+
+        let learned = loss::<8, 2, 2, 3, usize>(&history, &alpha, &FRAME).unwrap();
+        let guessed = loss::<8, 2, 2, 3, usize>(&history, &[0.5, 0.5], &FRAME).unwrap();
+        assert!(learned < guessed);
+    }
+}
@@ -1,6 +1,10 @@
 //! Core DST operations: `bel` and `pl` corresponding to the
 //! calculation of belief and plausabilty respectivey.
-use crate::{approx::Approximation, comb::CombRule, set::Set};
+use crate::{
+    approx::Approximation,
+    comb::{CombError, CombRule},
+    set::{Bitset, Complement, Set},
+};
 use core::{iter::Sum, ops::Sub};
 
 /// Compute the belief of `Q` given a BBA.
@@ -17,43 +21,75 @@ where
 /// Compute the plausability of 'Q' given a BBA.
 pub fn pl<'a, S, T>(bba: impl IntoIterator<Item = &'a (S, T)>, q: &S) -> T
 where
-    S: Set + 'a,
+    S: Complement + 'a,
     T: Sum<&'a T> + From<u8> + Sub<Output = T> + 'a,
 {
     T::from(1u8) - bel(bba, &q.not())
 }
 
+/// Compute the pignistic probability of `Q` given a BBA: each focal set's mass is
+/// spread evenly over its elements, excluding any mass on the empty set.
+pub fn betp<'a, S>(bba: impl IntoIterator<Item = &'a (S, f32)>, q: &S) -> f32
+where
+    S: Bitset + 'a,
+{
+    let (num, empty) = bba.into_iter().fold((0f32, 0f32), |(num, empty), (p, m)| {
+        match p.card() {
+            0 => (num, empty + m), // m(\emptyset)
+            c => (num + m * S::cap(p, q).card() as f32 / c as f32, empty),
+        }
+    });
+
+    num / (1f32 - empty)
+}
+
 /// Combine a set of BBAs with an approximation and combination rule.
 pub fn comb_approx<'a, const N: usize, S, T, A, C>(
     // TODO: The above takes a reference, but this one consumes. `Approximation`
     // in some sense needs to consume; we could take a reference and immediately copy
     // it?
     bba: impl IntoIterator<Item = impl IntoIterator<Item = (S, T)>>,
-) -> [(S, T); N]
+) -> Result<[(S, T); N], CombError>
 where
     S: Set + 'a,
     T: From<u8> + 'a,
     A: Approximation<S, T>,
     C: CombRule<S, T>,
 {
+    let mut bba = bba.into_iter().map(|e| A::approx(e)); // Compute the initial approximation.
+    let init: [Option<(S, T)>; N] = bba.next().expect("Called combination on an empty BBA?");
+
     let mut iter = bba
-        .into_iter()
-        .map(|e| A::approx(e)) // Compute the initial approximation.
-        .reduce(|acc: [Option<(S, T)>; N], e| A::approx(C::comb(&acc, &e))) // Proceed to combine them, then approximate again.
-        .expect("Called combination on an empty BBA?")
+        .try_fold(init, |acc, e| C::comb(&acc, &e).map(A::approx))? // Proceed to combine them, then approximate again.
         .into_iter()
         .flatten();
 
-    core::array::from_fn(|_| iter.next().unwrap_or((S::EMPTY, 0u8.into())))
+    Ok(core::array::from_fn(|_| {
+        iter.next().unwrap_or((S::EMPTY, 0u8.into()))
+    }))
+}
+
+/// Combine an `N`-element BBA with an `M`-element BBA, approximating the result to
+/// `O` elements; unlike `comb_approx`, neither input is approximated beforehand.
+pub fn comb_sized<const O: usize, const N: usize, const M: usize, S, T, A, C>(
+    a: &[Option<(S, T)>; N],
+    b: &[Option<(S, T)>; M],
+) -> Result<[Option<(S, T)>; O], CombError>
+where
+    S: Set,
+    A: Approximation<S, T>,
+    C: CombRule<S, T>,
+{
+    Ok(A::approx(C::comb(a, b)?))
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     const TOL: f32 = 0.001;
 
-    pub(super) mod traffic_light {
+    pub(crate) mod traffic_light {
         // A test case corresponding to the colors on a traffic
         // light; found on Wikipedia.
         pub const RED: usize = 0b100;
@@ -71,6 +107,14 @@ mod tests {
         ];
     }
 
+    pub(crate) mod abc {
+        // A frame of three hypotheses, for BBAs made up to suit a test.
+        pub const A: usize = 0b001;
+        pub const B: usize = 0b010;
+        pub const C: usize = 0b100;
+        pub const FRAME: usize = A | B | C;
+    }
+
     #[test]
     fn test_bel() {
         use traffic_light::*;
@@ -83,6 +127,17 @@ mod tests {
         assert_eq!(bel(TRAFFIC_BBA, &(RED | YELLOW | GREEN)), 1.0f32);
     }
 
+    #[test]
+    fn test_betp() {
+        use traffic_light::*;
+        // 0.35 + 0.06 / 2 + 0.05 / 2 + 0.1 / 3
+        assert!((betp(TRAFFIC_BBA, &RED) - 0.4383f32).abs() < TOL);
+        assert!((betp(TRAFFIC_BBA, &(RED | YELLOW | GREEN)) - 1.0f32).abs() < TOL);
+
+        let conflicting = [(0usize, 0.5f32), (RED, 0.25f32), (RED | GREEN, 0.25f32)];
+        assert!((betp(&conflicting, &RED) - 0.75f32).abs() < TOL);
+    }
+
     #[test]
     fn test_pl() {
         use traffic_light::*;
@@ -94,4 +149,33 @@ mod tests {
         assert!((pl(TRAFFIC_BBA, &(YELLOW | GREEN)) - 0.65f32).abs() < TOL);
         assert!((pl(TRAFFIC_BBA, &(RED | YELLOW | GREEN)) - 1.0f32).abs() < TOL);
     }
+
+    #[test]
+    fn test_comb_sized() {
+        use crate::approx::KX;
+        use crate::comb::Dempster;
+        use traffic_light::*;
+
+        // A detailed source alongside a coarse one: "it's not green".
+        let fine: [Option<(usize, f32)>; 7] = core::array::from_fn(|i| Some(TRAFFIC_BBA[i]));
+        let coarse = [
+            Some((RED | YELLOW, 0.9f32)),
+            Some((RED | YELLOW | GREEN, 0.1f32)),
+        ];
+
+        let exact = comb_sized::<16, 7, 2, usize, f32, KX, Dempster>(&fine, &coarse).unwrap();
+        let exact: [(usize, f32); 16] = exact.map(|x| x.unwrap_or((0, 0f32)));
+        let truncated = comb_approx::<2, usize, f32, KX, Dempster>([
+            fine.iter().flatten().copied().collect::<Vec<_>>(),
+            coarse.iter().flatten().copied().collect(),
+        ])
+        .unwrap();
+
+        // K = 0.9 * m(green); yellow keeps its own 0.25 and gains 0.9 * m(yellow | green).
+        let k = 0.9f32 * 0.15f32;
+        assert!((bel(&exact, &YELLOW) - (0.25f32 + 0.036f32) / (1f32 - k)).abs() < TOL);
+        assert!((bel(&exact, &(RED | YELLOW | GREEN)) - 1f32).abs() < TOL);
+        // Truncating the fine source to two elements first distorts the result.
+        assert!((bel(&truncated, &YELLOW) - bel(&exact, &YELLOW)).abs() > 0.05f32);
+    }
 }
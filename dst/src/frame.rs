@@ -0,0 +1,224 @@
+//! Moving evidence between frames of discernment.
+//!
+//! A refining `\rho : 2^\Theta \to 2^\Omega` maps each singleton of a coarse frame
+//! `\Theta` onto a non-empty block of a fine frame `\Omega`, such that the blocks
+//! partition `\Omega`. Everything else follows from it:
+//! 1. Refining a BBA carries each focal set `A` to `\rho(A)`.
+//! 2. Coarsening a BBA carries each focal set `B` to its outer reduction: those
+//!    `\theta` whose block intersects `B`.
+//! 3. A product frame `\Theta \times \Omega` is a refinement of both `\Theta` and
+//!    `\Omega` via cylinders; refining onto it is the vacuous extension, and
+//!    coarsening from it is the marginalization.
+use crate::container::hm::SummationHM;
+use crate::set::Set;
+use core::hash::Hash;
+use core::ops::{AddAssign, MulAssign};
+
+/// A refining from a coarse frame `C` onto a fine frame `F` with `K` singletons.
+#[derive(Debug)]
+pub struct Refining<const K: usize, C, F> {
+    blocks: [(C, F); K],
+}
+
+impl<const K: usize, C, F> Refining<K, C, F>
+where
+    C: Set,
+    F: Set,
+{
+    /// Construct a `Refining` from `(singleton, block)` pairs; the singletons and the
+    /// blocks should both be non-empty and pairwise disjoint.
+    pub fn build(blocks: [(C, F); K]) -> Self {
+        let disjoint = |i: usize, j: usize| {
+            let (a, b) = (&blocks[i], &blocks[j]);
+            C::cap(&a.0, &b.0) == C::EMPTY && F::cap(&a.1, &b.1) == F::EMPTY
+        };
+        assert!(blocks.iter().all(|(c, f)| *c != C::EMPTY && *f != F::EMPTY));
+        assert!((0..K).all(|i| (i + 1..K).all(|j| disjoint(i, j))));
+        Self { blocks }
+    }
+
+    /// Refine a set within the coarse frame: `\rho(A) = \cup_{\theta \in A} \rho(\theta)`.
+    pub fn refine(&self, a: &C) -> F {
+        refine(&self.blocks, a)
+    }
+
+    /// Coarsen a set within the fine frame to its outer reduction: those `\theta`
+    /// where `\rho(\theta) \cap B \neq \emptyset`.
+    pub fn coarsen_outer(&self, b: &F) -> C {
+        coarsen_outer(&self.blocks, b)
+    }
+
+    /// Coarsen a set within the fine frame to its inner reduction: those `\theta`
+    /// where `\rho(\theta) \subseteq B`.
+    pub fn coarsen_inner(&self, b: &F) -> C {
+        self.blocks
+            .iter()
+            .filter(|(_, f)| f.is_subset(b))
+            .fold(C::EMPTY, |acc, (c, _)| C::cup(&acc, c))
+    }
+
+    /// Carry a BBA onto the fine frame; with a cylinder (see `cylinders`) this is the
+    /// vacuous extension onto the product frame.
+    pub fn refine_bba<const N: usize, T>(&self, bba: [Option<(C, T)>; N]) -> [Option<(F, T)>; N] {
+        refine_bba(&self.blocks, bba)
+    }
+
+    /// Carry a BBA onto the coarse frame via the outer reduction; with a cylinder (see
+    /// `cylinders`) this is the marginalization onto the component frame.
+    pub fn coarsen_bba<const N: usize, T>(&self, bba: [Option<(F, T)>; N]) -> [Option<(C, T)>; N]
+    where
+        C: Hash + Eq,
+        T: AddAssign + MulAssign,
+    {
+        coarsen_bba(&self.blocks, bba)
+    }
+}
+
+// The below operate on the `(singleton, block)` pairs directly, so that a refining
+// whose size isn't known at compile time can share them.
+
+pub(crate) fn refine<C: Set, F: Set>(blocks: &[(C, F)], a: &C) -> F {
+    blocks
+        .iter()
+        .filter(|(c, _)| c.is_subset(a))
+        .fold(F::EMPTY, |acc, (_, f)| F::cup(&acc, f))
+}
+
+pub(crate) fn coarsen_outer<C: Set, F: Set>(blocks: &[(C, F)], b: &F) -> C {
+    blocks
+        .iter()
+        .filter(|(_, f)| F::cap(f, b) != F::EMPTY)
+        .fold(C::EMPTY, |acc, (c, _)| C::cup(&acc, c))
+}
+
+pub(crate) fn refine_bba<const N: usize, C: Set, F: Set, T>(
+    blocks: &[(C, F)],
+    bba: [Option<(C, T)>; N],
+) -> [Option<(F, T)>; N] {
+    bba.map(|x| x.map(|(a, m)| (refine(blocks, &a), m)))
+}
+
+pub(crate) fn coarsen_bba<const N: usize, C, F, T>(
+    blocks: &[(C, F)],
+    bba: [Option<(F, T)>; N],
+) -> [Option<(C, T)>; N]
+where
+    C: Set + Hash + Eq,
+    F: Set,
+    T: AddAssign + MulAssign,
+{
+    // Distinct focal sets can collapse onto the same reduction; there's never more
+    // than `N` of them, so the map can't overflow.
+    let mut map: SummationHM<1, N, C, T> = SummationHM::default();
+    for (b, m) in bba.into_iter().flatten() {
+        map.insert(coarsen_outer(blocks, &b), m)
+            .expect("At most N distinct reductions.");
+    }
+
+    let mut iter = map.consume();
+    core::array::from_fn(|_| iter.next())
+}
+
+/// The cylinder refinings of the bitset frames `\Theta` (of `A` singletons) and
+/// `\Omega` (of `B` singletons) onto `\Theta \times \Omega`, where the pair
+/// `(\theta_i, \omega_j)` is bit `i * B + j`.
+pub fn cylinders<const A: usize, const B: usize>(
+) -> (Refining<A, usize, usize>, Refining<B, usize, usize>) {
+    assert!(A * B <= usize::BITS as usize);
+    let row = usize::MAX >> (usize::BITS as usize - B); // \{\theta_0\} \times \Omega.
+    let col = (0..A).fold(0usize, |acc, i| acc | 1 << (i * B)); // \Theta \times \{\omega_0\}.
+
+    (
+        Refining::build(core::array::from_fn(|i| (1 << i, row << (i * B)))),
+        Refining::build(core::array::from_fn(|j| (1 << j, col << j))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::KX;
+    use crate::comb::Dempster;
+    use crate::dst::{bel, comb_approx, pl};
+
+    const TOL: f32 = 0.001;
+
+    // Target class \times threat level.
+    const CAR: usize = 0b001;
+    const TRUCK: usize = 0b010;
+    const PERSON: usize = 0b100;
+    const LOW: usize = 0b01;
+    const HIGH: usize = 0b10;
+
+    #[test]
+    fn test_refine_coarsen() {
+        // {vehicle, person} -> {car, truck, person}.
+        let r = Refining::build([(0b01usize, CAR | TRUCK), (0b10, PERSON)]);
+        assert_eq!(r.refine(&0b01), CAR | TRUCK);
+        assert_eq!(r.refine(&0b11), CAR | TRUCK | PERSON);
+        assert_eq!(r.coarsen_outer(&CAR), 0b01);
+        assert_eq!(r.coarsen_outer(&(CAR | PERSON)), 0b11);
+        assert_eq!(r.coarsen_inner(&(CAR | PERSON)), 0b10);
+        assert_eq!(r.coarsen_inner(&(CAR | TRUCK)), 0b01);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_refining_overlap() {
+        Refining::build([(0b01usize, CAR | TRUCK), (0b10, TRUCK | PERSON)]);
+    }
+
+    #[test]
+    fn test_cylinders() {
+        let (class, threat) = cylinders::<3, 2>();
+        // (car, low) is bit 0, (car, high) is bit 1, (truck, low) is bit 2, ...
+        assert_eq!(class.refine(&CAR), 0b000011);
+        assert_eq!(class.refine(&PERSON), 0b110000);
+        assert_eq!(threat.refine(&LOW), 0b010101);
+        assert_eq!(threat.refine(&HIGH), 0b101010);
+        assert_eq!(class.coarsen_outer(&0b100001), CAR | PERSON);
+        assert_eq!(threat.coarsen_outer(&0b100001), LOW | HIGH);
+    }
+
+    #[test]
+    fn test_extend_marginalize() {
+        let (class, _) = cylinders::<3, 2>();
+        let bba = [Some((CAR, 0.6f32)), Some((CAR | TRUCK, 0.3f32)), None];
+        let bba_ext = class.refine_bba(bba);
+        assert_eq!(bba_ext[1], Some((0b001111, 0.3f32)));
+
+        // Marginalizing a vacuous extension gives back the original.
+        let bba_marg = class.coarsen_bba(bba_ext);
+        for elem in bba.iter().flatten() {
+            assert!(bba_marg.iter().flatten().any(|x| x == elem));
+        }
+    }
+
+    #[test]
+    fn test_joint_fusion() {
+        let (class, threat) = cylinders::<3, 2>();
+        let m_class = [Some((PERSON, 0.8f32)), Some((CAR | TRUCK | PERSON, 0.2f32))];
+        let m_threat = [Some((HIGH, 0.5f32)), Some((LOW | HIGH, 0.5f32))];
+
+        // A relation on the joint frame: persons are low threat.
+        let rel = [
+            Some(((0b111111 & !class.refine(&PERSON)) | 0b010000, 0.9f32)),
+            Some((0b111111, 0.1f32)),
+        ];
+
+        let joint = comb_approx::<4, usize, f32, KX, Dempster>([
+            class.refine_bba(m_class).into_iter().flatten(),
+            threat.refine_bba(m_threat).into_iter().flatten(),
+            rel.into_iter().flatten(),
+        ])
+        .unwrap();
+
+        // Dempster over the joint frame, then marginalize onto the threat.
+        let m = threat.coarsen_bba(joint.map(Some));
+        let m: [(usize, f32); 4] = m.map(|x| x.unwrap_or((0, 0.0)));
+        // The person is likely, so the relation pulls mass towards a low threat.
+        assert!(bel(&m, &LOW) > bel(&m, &HIGH));
+        assert!(bel(&m, &HIGH) < pl(&m, &HIGH));
+        assert!((bel(&m, &(LOW | HIGH)) - 1.0f32).abs() < TOL);
+    }
+}
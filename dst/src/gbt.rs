@@ -0,0 +1,145 @@
+//! Smets' Generalized Bayesian Theorem.
+//!
+//! Given a conditional BBA `m[\theta_i]` over an observation frame `X` for each class
+//! `\theta_i` (bit `i` of the class frame), and an observation `x \subseteq X`, the
+//! posterior over the classes is the conjunctive combination of the BBAs
+//! `m_i(\Theta) = pl[\theta_i](x)`, `m_i(\Theta \setminus \{\theta_i\}) = 1 - pl[\theta_i](x)`;
+//! normalizing that combination is exactly `Dempster`.
+//!
+//! P. Smets, "Belief functions: the disjunctive rule of combination and the
+//! generalized Bayesian theorem", International Journal of Approximate Reasoning, 1993.
+use crate::approx::Approximation;
+use crate::comb::{CombError, Dempster};
+use crate::dst::{self, comb_approx};
+use crate::set::{Bitset, Complement};
+use core::hash::Hash;
+
+/// Compute `1 - pl[\theta_i](x)` for each class.
+fn implausibility<const M: usize, const K: usize, X: Complement>(
+    conditionals: &[[(X, f32); M]; K],
+    x: &X,
+) -> [f32; K] {
+    core::array::from_fn(|i| 1f32 - dst::pl(&conditionals[i], x))
+}
+
+/// Compute the posterior BBA over the `K` classes, approximated to `N` elements; there
+/// are up to `2^K` focal sets, so `N = 1 << K` avoids any approximation.
+pub fn posterior<const N: usize, const M: usize, const K: usize, X, S, A>(
+    conditionals: &[[(X, f32); M]; K],
+    x: &X,
+) -> Result<[(S, f32); N], CombError>
+where
+    X: Complement,
+    S: Bitset + Hash + Eq + Clone,
+    A: Approximation<S, f32>,
+{
+    let q = implausibility(conditionals, x);
+    if q.iter().all(|q_i| *q_i == 1f32) {
+        return Err(CombError::Conflict); // `x` is implausible under every class.
+    }
+
+    let theta = (0..K).fold(S::EMPTY, |acc, i| S::cup(&acc, &S::singleton(i)));
+    comb_approx::<N, S, f32, A, Dempster>(q.into_iter().enumerate().map(|(i, q_i)| {
+        let others = S::cap(&theta, &S::singleton(i).not());
+        [(theta.clone(), 1f32 - q_i), (others, q_i)]
+    }))
+}
+
+/// Compute the posterior belief of `A` in closed form:
+/// `bel(A) \propto \prod_{\theta_i \notin A} (1 - pl[\theta_i](x)) - \prod_i (1 - pl[\theta_i](x))`.
+pub fn posterior_bel<const M: usize, const K: usize, X, S>(
+    conditionals: &[[(X, f32); M]; K],
+    x: &X,
+    a: &S,
+) -> f32
+where
+    X: Complement,
+    S: Bitset,
+{
+    let q = implausibility(conditionals, x);
+    let empty: f32 = q.iter().product();
+    let outside: f32 = (0..K)
+        .filter(|i| !S::singleton(*i).is_subset(a))
+        .map(|i| q[i])
+        .product();
+
+    (outside - empty) / (1f32 - empty)
+}
+
+/// Compute the posterior plausibility of `A` in closed form:
+/// `pl(A) \propto 1 - \prod_{\theta_i \in A} (1 - pl[\theta_i](x))`.
+pub fn posterior_pl<const M: usize, const K: usize, X, S>(
+    conditionals: &[[(X, f32); M]; K],
+    x: &X,
+    a: &S,
+) -> f32
+where
+    X: Complement,
+    S: Bitset,
+{
+    let q = implausibility(conditionals, x);
+    let empty: f32 = q.iter().product();
+    let inside: f32 = (0..K)
+        .filter(|i| S::singleton(*i).is_subset(a))
+        .map(|i| q[i])
+        .product();
+
+    (1f32 - inside) / (1f32 - empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::KX;
+    use crate::dst::{bel, pl};
+
+    const TOL: f32 = 0.001;
+
+    // Observations: small, medium, large.
+    const SMALL: usize = 0b001;
+    const MEDIUM: usize = 0b010;
+    const LARGE: usize = 0b100;
+    const X: usize = SMALL | MEDIUM | LARGE;
+
+    // Classes: car, truck, person.
+    const CAR: usize = 0b001;
+    const TRUCK: usize = 0b010;
+    const PERSON: usize = 0b100;
+
+    const CONDITIONALS: [[(usize, f32); 3]; 3] = [
+        [(MEDIUM, 0.6), (MEDIUM | LARGE, 0.3), (X, 0.1)],
+        [(LARGE, 0.8), (X, 0.2), (0, 0.0)],
+        [(SMALL, 0.9), (X, 0.1), (0, 0.0)],
+    ];
+
+    #[test]
+    fn test_posterior_closed_form() {
+        // pl[car](large) = 0.4, pl[truck](large) = 1.0, pl[person](large) = 0.1.
+        assert!((posterior_pl(&CONDITIONALS, &LARGE, &TRUCK) - 1.0f32).abs() < TOL);
+        assert!((posterior_pl(&CONDITIONALS, &LARGE, &CAR) - 0.4f32).abs() < TOL);
+        assert!((posterior_pl(&CONDITIONALS, &LARGE, &PERSON) - 0.1f32).abs() < TOL);
+        assert!((posterior_bel(&CONDITIONALS, &LARGE, &TRUCK) - 0.54f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_posterior_bba() {
+        for x in [SMALL, MEDIUM, LARGE, MEDIUM | LARGE] {
+            let bba = posterior::<8, 3, 3, usize, usize, KX>(&CONDITIONALS, &x).unwrap();
+            for a in 1usize..8 {
+                let (b, p) = (bel(&bba, &a), pl(&bba, &a));
+                assert!((b - posterior_bel(&CONDITIONALS, &x, &a)).abs() < TOL);
+                assert!((p - posterior_pl(&CONDITIONALS, &x, &a)).abs() < TOL);
+            }
+        }
+    }
+
+    #[test]
+    fn test_posterior_conflict() {
+        // Nothing explains an observation that's implausible under every class.
+        let conditionals = [[(SMALL, 1.0f32)], [(MEDIUM, 1.0f32)]];
+        assert_eq!(
+            posterior::<4, 1, 2, usize, usize, KX>(&conditionals, &LARGE).unwrap_err(),
+            CombError::Conflict
+        );
+    }
+}
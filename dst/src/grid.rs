@@ -0,0 +1,303 @@
+//! Evidential occupancy grid mapping.
+//!
+//! Each cell holds a BBA over `\{free, occupied\}`. A range reading is cast as a ray
+//! from the sensor: the cells it passes through are evidence of `free`, and the cell
+//! it ends within, should it have hit something, is evidence of `occupied`. Evidence
+//! is fused with a `CombRule`; the conflict between a cell and each reading is kept,
+//! as it's what a moving object leaves behind.
+//!
+//! The conflict of a cell accumulates as `c \leftarrow 1 - (1 - c)(1 - K)` for each
+//! reading's conflict `K`, and fades by a factor with each call to `Grid::decay`; once
+//! per scan, say, so that a cell the object has left behind clears over time.
+//!
+//! J. Moras, V. Cherfaoui, P. Bonnifait, "Moving objects detection by conflict
+//! analysis in evidential grids", IEEE Intelligent Vehicles Symposium, 2011.
+use crate::approx::KX;
+use crate::comb::{CombError, CombRule, Dempster};
+use crate::dst::{self, comb_sized};
+use crate::set::{Bitset, Complement, Set};
+use core::marker::PhantomData;
+
+/// A subset of `\{free, occupied\}` as two bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Occupancy(u8);
+
+impl Occupancy {
+    /// The cell is free.
+    pub const FREE: Self = Self(0b01);
+    /// The cell is occupied.
+    pub const OCCUPIED: Self = Self(0b10);
+    /// Nothing is known of the cell.
+    pub const UNKNOWN: Self = Self(0b11);
+}
+
+impl Set for Occupancy {
+    fn is_subset(&self, rhs: &Self) -> bool {
+        self.0 & rhs.0 == self.0
+    }
+
+    fn cap(lhs: &Self, rhs: &Self) -> Self {
+        Self(lhs.0 & rhs.0)
+    }
+
+    fn cup(lhs: &Self, rhs: &Self) -> Self {
+        Self(lhs.0 | rhs.0)
+    }
+
+    const EMPTY: Self = Self(0u8);
+}
+
+impl Complement for Occupancy {
+    fn not(&self) -> Self {
+        Self(!self.0 & 0b11)
+    }
+}
+
+impl Bitset for Occupancy {
+    const BITS: usize = 2;
+
+    fn card(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    fn singleton(i: usize) -> Self {
+        assert!(i < Self::BITS);
+        Self(1 << i)
+    }
+}
+
+/// The BBA of a cell; there are only four subsets of the frame.
+pub type Cell = [Option<(Occupancy, f32)>; 4];
+
+/// The inverse sensor model: the mass a reading assigns to each cell it informs.
+#[derive(Debug, Clone, Copy)]
+pub struct SensorModel {
+    free: f32,
+    occupied: f32,
+}
+
+impl SensorModel {
+    /// Construct the model from `m(\{free\})` for a cell the ray passes through and
+    /// `m(\{occupied\})` for the cell a hit ends within; each in `[0, 1)`, as a reading
+    /// that's certain could never be revised.
+    pub fn build(free: f32, occupied: f32) -> Self {
+        assert!((0f32..1f32).contains(&free));
+        assert!((0f32..1f32).contains(&occupied));
+        Self { free, occupied }
+    }
+}
+
+/// A `W` by `H` grid, fused with the rule `C`.
+pub struct Grid<const W: usize, const H: usize, C = Dempster> {
+    cells: [[Cell; W]; H],
+    conflict: [[f32; W]; H],
+    rule: PhantomData<C>,
+}
+
+impl<const W: usize, const H: usize, C> Default for Grid<W, H, C> {
+    fn default() -> Self {
+        let vacuous: Cell = [Some((Occupancy::UNKNOWN, 1f32)), None, None, None];
+        Self {
+            cells: [[vacuous; W]; H],
+            conflict: [[0f32; W]; H],
+            rule: PhantomData,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, C> Grid<W, H, C>
+where
+    C: CombRule<Occupancy, f32>,
+{
+    /// Fuse a simple support function upon `focal` into the cell `(x, y)`.
+    fn update(
+        &mut self,
+        (x, y): (usize, usize),
+        focal: Occupancy,
+        m: f32,
+    ) -> Result<(), CombError> {
+        let reading = [Some((focal, m)), Some((Occupancy::UNKNOWN, 1f32 - m))];
+        let cell = &mut self.cells[y][x];
+
+        // \sum_{A \cap B = \emptyset} m_1(A) m_2(B), whatever the rule does with it.
+        let k: f32 = cell
+            .iter()
+            .flatten()
+            .flat_map(|a| reading.iter().flatten().map(move |b| (a, b)))
+            .filter(|(a, b)| Occupancy::cap(&a.0, &b.0) == Occupancy::EMPTY)
+            .map(|(a, b)| a.1 * b.1)
+            .sum();
+
+        // Leave the cell as is, rather than poisoned, should the rule fail to normalize.
+        let fused = comb_sized::<4, 4, 2, Occupancy, f32, KX, C>(cell, &reading)?;
+        if fused.iter().flatten().any(|x| !x.1.is_finite()) {
+            return Err(CombError::Conflict);
+        }
+
+        *cell = fused;
+        let c = &mut self.conflict[y][x];
+        *c = 1f32 - (1f32 - *c) * (1f32 - k);
+        Ok(())
+    }
+
+    /// Cast a reading from the cell `from` to the cell `to`; the cells along the way
+    /// are free, and `to` is occupied should the reading be a `hit`, or free otherwise.
+    pub fn ray(
+        &mut self,
+        from: (usize, usize),
+        to: (usize, usize),
+        hit: bool,
+        model: &SensorModel,
+    ) -> Result<(), CombError> {
+        assert!(from.0 < W && to.0 < W && from.1 < H && to.1 < H);
+
+        // Bresenham's line algorithm, over every octant.
+        let (x1, y1) = (to.0 as isize, to.1 as isize);
+        let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+        let mut err = dx + dy;
+
+        while (x, y) != (x1, y1) {
+            self.update((x as usize, y as usize), Occupancy::FREE, model.free)?;
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+
+        match hit {
+            true => self.update(to, Occupancy::OCCUPIED, model.occupied),
+            false => self.update(to, Occupancy::FREE, model.free),
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, C> Grid<W, H, C> {
+    /// Fetch the BBA of the cell `(x, y)`.
+    pub fn cell(&self, x: usize, y: usize) -> &Cell {
+        &self.cells[y][x]
+    }
+
+    /// Compute the belief of `Q` for the cell `(x, y)`.
+    pub fn bel(&self, x: usize, y: usize, q: &Occupancy) -> f32 {
+        dst::bel(self.cells[y][x].iter().flatten(), q)
+    }
+
+    /// Compute the plausibility of `Q` for the cell `(x, y)`.
+    pub fn pl(&self, x: usize, y: usize, q: &Occupancy) -> f32 {
+        dst::pl(self.cells[y][x].iter().flatten(), q)
+    }
+
+    /// Compute the belief of `Q` for every cell.
+    pub fn bel_map(&self, q: &Occupancy) -> [[f32; W]; H] {
+        core::array::from_fn(|y| core::array::from_fn(|x| self.bel(x, y, q)))
+    }
+
+    /// Compute the plausibility of `Q` for every cell.
+    pub fn pl_map(&self, q: &Occupancy) -> [[f32; W]; H] {
+        core::array::from_fn(|y| core::array::from_fn(|x| self.pl(x, y, q)))
+    }
+
+    /// The accumulated conflict of each cell; see `decay`.
+    pub fn conflict_map(&self) -> &[[f32; W]; H] {
+        &self.conflict
+    }
+
+    /// Fade the conflict of every cell by `factor` in `[0, 1]`.
+    pub fn decay(&mut self, factor: f32) {
+        assert!((0f32..=1f32).contains(&factor));
+        self.conflict
+            .iter_mut()
+            .flatten()
+            .for_each(|c| *c *= factor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOL: f32 = 0.001;
+
+    fn model() -> SensorModel {
+        SensorModel::build(0.6, 0.8)
+    }
+
+    #[test]
+    fn test_ray() {
+        let mut grid = Grid::<8, 8>::default();
+        grid.ray((0, 0), (4, 2), true, &model()).unwrap();
+
+        // The endpoint is occupied, the ray's cells free, and the rest unknown.
+        assert!((grid.bel(4, 2, &Occupancy::OCCUPIED) - 0.8f32).abs() < TOL);
+        assert!((grid.bel(0, 0, &Occupancy::FREE) - 0.6f32).abs() < TOL);
+        assert!((grid.bel(2, 1, &Occupancy::FREE) - 0.6f32).abs() < TOL);
+        assert!((grid.pl(7, 7, &Occupancy::OCCUPIED) - 1f32).abs() < TOL);
+        assert!(grid.bel(7, 7, &Occupancy::FREE).abs() < TOL);
+
+        let free = grid.bel_map(&Occupancy::FREE);
+        assert_eq!(free.iter().flatten().filter(|b| **b > 0f32).count(), 4);
+    }
+
+    #[test]
+    fn test_accumulate() {
+        let mut grid = Grid::<4, 4>::default();
+        for _ in 0..3 {
+            grid.ray((0, 3), (3, 3), true, &model()).unwrap();
+        }
+
+        // 1 - 0.2^3.
+        assert!((grid.bel(3, 3, &Occupancy::OCCUPIED) - 0.992f32).abs() < TOL);
+        assert!(grid.conflict_map().iter().flatten().all(|k| k.abs() < TOL));
+    }
+
+    #[test]
+    fn test_dynamic_conflict() {
+        // Something passes through a cell that was seen free.
+        let mut grid = Grid::<4, 4>::default();
+        grid.ray((0, 0), (3, 0), false, &model()).unwrap();
+        grid.ray((0, 1), (2, 0), true, &model()).unwrap();
+
+        assert!((grid.conflict_map()[0][2] - 0.6f32 * 0.8f32).abs() < TOL);
+        let (b, p) = (
+            grid.bel(2, 0, &Occupancy::OCCUPIED),
+            grid.pl(2, 0, &Occupancy::OCCUPIED),
+        );
+        assert!(b > 0f32 && b < p);
+
+        // Agreeing readings don't erase it; only decay does.
+        let k = grid.conflict_map()[0][2];
+        grid.ray((0, 1), (2, 0), true, &model()).unwrap();
+        assert!(grid.conflict_map()[0][2] >= k);
+        grid.decay(0.5);
+        assert!(grid.conflict_map()[0][2] < k);
+        assert!(grid.conflict_map()[3][3].abs() < TOL);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_certain_model() {
+        SensorModel::build(1.0, 1.0);
+    }
+
+    #[test]
+    fn test_occupancy_set() {
+        use crate::dst::betp;
+        assert_eq!(Occupancy::FREE.not(), Occupancy::OCCUPIED);
+        assert_eq!(Occupancy::singleton(1), Occupancy::OCCUPIED);
+        assert_eq!(Occupancy::UNKNOWN.card(), 2);
+        let m = [(Occupancy::UNKNOWN, 1f32)];
+        assert!((betp(&m, &Occupancy::FREE) - 0.5f32).abs() < TOL);
+    }
+}
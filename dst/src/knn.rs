@@ -0,0 +1,159 @@
+//! Denœux's evidential k-nearest-neighbour classifier.
+//!
+//! Each of the `K` nearest labelled samples is a piece of evidence: a simple
+//! support function on its class, whose mass `\alpha e^{-\gamma d^2}` decays with
+//! the distance `d`, and the remainder on the frame. These are fused via `Dempster`.
+//!
+//! T. Denœux, "A k-nearest neighbor classification rule based on Dempster-Shafer
+//! theory", IEEE Transactions on Systems, Man, and Cybernetics, 1995.
+use crate::approx::KX;
+use crate::comb::{CombError, Dempster};
+use crate::container::heap::PriorityHeap;
+use crate::dst::{bel, betp, comb_approx, pl};
+use crate::set::Bitset;
+use core::hash::Hash;
+
+/// An evidential k-NN classifier over the frame `S` considering `K` neighbours.
+#[derive(Debug)]
+pub struct EvidentialKNN<const K: usize, S> {
+    frame: S,
+    alpha: f32,
+    gamma: f32,
+}
+
+/// The result of a classification.
+#[derive(Debug)]
+pub struct Classification<const N: usize, S> {
+    /// The fused BBA.
+    pub bba: [(S, f32); N],
+    /// The class with the largest pignistic probability.
+    pub decision: S,
+}
+
+impl<const N: usize, S> Classification<N, S>
+where
+    S: Bitset,
+{
+    /// Compute the belief interval `[bel(Q), pl(Q)]`.
+    pub fn interval(&self, q: &S) -> (f32, f32) {
+        (bel(&self.bba, q), pl(&self.bba, q))
+    }
+}
+
+impl<const K: usize, S> EvidentialKNN<K, S>
+where
+    S: Bitset + Hash + Eq + Clone,
+{
+    /// Construct the classifier; `alpha` in `(0, 1)` caps the mass a neighbour can
+    /// contribute, and `gamma` is the rate at which it decays with distance.
+    pub fn build(frame: S, alpha: f32, gamma: f32) -> Self {
+        assert!(alpha > 0f32 && alpha < 1f32);
+        assert!(gamma > 0f32);
+        Self {
+            frame,
+            alpha,
+            gamma,
+        }
+    }
+
+    /// Classify `x` from labelled samples, where each label is a singleton of the
+    /// frame; `N` should exceed the number of classes to avoid any approximation.
+    pub fn classify<const N: usize, const D: usize>(
+        &self,
+        samples: &[([f32; D], S)],
+        x: &[f32; D],
+    ) -> Result<Classification<N, S>, CombError> {
+        assert!(
+            !samples.is_empty(),
+            "Should have at least one labelled sample."
+        );
+
+        // Utilize a PH to capture the K nearest samples; the heap retains the largest
+        // keys, so the key is the negated squared distance.
+        let f = |n: &(f32, &S)| -n.0;
+        let mut neighbours = PriorityHeap::<K, (f32, &S)>::default();
+        for (p, label) in samples {
+            let d2: f32 = p.iter().zip(x).map(|(a, b)| (a - b) * (a - b)).sum();
+            neighbours.insert_by_key(f, (d2, label));
+        }
+
+        // m_i(\{\omega_q\}) = \alpha e^{-\gamma d_i^2}, m_i(\Omega) = 1 - m_i(\{\omega_q\}).
+        let bba = comb_approx::<N, S, f32, KX, Dempster>(
+            neighbours
+                .consume_sorted_by_key(f)
+                .into_iter()
+                .flatten()
+                .map(|(d2, label)| {
+                    let m = self.alpha * libm::expf(-self.gamma * d2);
+                    [(label.clone(), m), (self.frame.clone(), 1f32 - m)]
+                }),
+        )?;
+
+        Ok(Classification {
+            decision: self.decide(&bba),
+            bba,
+        })
+    }
+
+    /// Compute the singleton of the frame with the largest pignistic probability.
+    fn decide(&self, bba: &[(S, f32)]) -> S {
+        (0..S::BITS)
+            .map(S::singleton)
+            .filter(|s| s.is_subset(&self.frame))
+            .map(|s| (betp(bba, &s), s))
+            .reduce(|max, e| if e.0 > max.0 { e } else { max })
+            .expect("Should have a non-empty frame.")
+            .1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dst::tests::abc::*;
+
+    const SAMPLES: [([f32; 2], usize); 9] = [
+        ([0.0, 0.0], A),
+        ([0.1, 0.2], A),
+        ([0.2, 0.1], A),
+        ([5.0, 5.0], B),
+        ([5.1, 4.8], B),
+        ([4.9, 5.2], B),
+        ([0.0, 5.0], C),
+        ([0.2, 5.1], C),
+        ([0.5, 4.0], C),
+    ];
+
+    #[test]
+    fn test_knn_decision() {
+        let knn = EvidentialKNN::<3, usize>::build(FRAME, 0.95, 1.0);
+        let a = knn.classify::<4, 2>(&SAMPLES, &[0.1, 0.1]).unwrap();
+        assert_eq!(a.decision, A);
+        let b = knn.classify::<4, 2>(&SAMPLES, &[4.5, 4.5]).unwrap();
+        assert_eq!(b.decision, B);
+
+        // Three near neighbours all agree: 1 - (1 - 0.95 e^{-\gamma d^2})^3 ~ 1.
+        let (bel_a, pl_a) = a.interval(&A);
+        assert!(bel_a > 0.95 && pl_a > 0.99);
+    }
+
+    #[test]
+    fn test_knn_ignorance() {
+        // Far from every sample, almost all mass remains upon the frame.
+        let knn = EvidentialKNN::<3, usize>::build(FRAME, 0.95, 1.0);
+        let far = knn.classify::<4, 2>(&SAMPLES, &[50.0, -50.0]).unwrap();
+        let (bel_a, pl_a) = far.interval(&A);
+        assert!(bel_a < 0.01 && pl_a > 0.99);
+        assert!(bel(&far.bba, &FRAME) > 0.99);
+    }
+
+    #[test]
+    fn test_knn_mixed() {
+        // Between A and C, with a neighbour of each, the interval widens.
+        let knn = EvidentialKNN::<2, usize>::build(FRAME, 0.95, 0.1);
+        let mid = knn.classify::<4, 2>(&SAMPLES, &[0.3, 2.3]).unwrap();
+        let (bel_c, pl_c) = mid.interval(&C);
+        assert!(bel_c > 0.1 && pl_c < 0.99 && bel_c < pl_c);
+        assert_eq!(mid.decision, C);
+    }
+}
@@ -1,8 +1,27 @@
 //! A Rust implementation of topics within Dempster-Shafer Theory.
 #![cfg_attr(not(test), no_std)]
 #![warn(missing_docs)]
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod approx;
+pub mod builder;
 pub mod comb;
 mod container;
+pub mod credal;
+pub mod dense;
+pub mod discount;
 pub mod dst;
+pub mod frame;
+pub mod gbt;
+pub mod grid;
+pub mod knn;
+pub mod mc;
+pub mod possibility;
+pub mod provenance;
+pub mod report;
+#[cfg(feature = "serde")]
+pub mod serde_array;
 pub mod set;
+#[cfg(feature = "alloc")]
+pub mod vbs;
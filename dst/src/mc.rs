@@ -0,0 +1,151 @@
+//! Monte Carlo estimation of Dempster's rule of combination.
+//!
+//! Rather than enumerate every intersection, draw a focal set from each source in
+//! proportion to its mass and intersect them; trials with an empty intersection are
+//! rejected, which is exactly Dempster's normalization. The proportion of accepted
+//! trials within (or intersecting) `Q` then estimates `bel(Q)` (or `pl(Q)`).
+//!
+//! S. Moral, N. Wilson, "Markov chain Monte-Carlo algorithms for the calculation of
+//! Dempster-Shafer belief", AAAI, 1994.
+use crate::comb::CombError;
+use crate::set::Set;
+use rng::Rng;
+
+/// An estimated proportion alongside its (Wilson score) confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// The point estimate.
+    pub mean: f32,
+    /// The lower bound of the interval.
+    pub lo: f32,
+    /// The upper bound of the interval.
+    pub hi: f32,
+}
+
+impl Estimate {
+    /// Construct the estimate of `k` successes from `n` trials, where `z` is the
+    /// standard normal quantile of the confidence level (1.96 for 95%).
+    fn wilson(k: usize, n: usize, z: f32) -> Self {
+        let (k, n) = (k as f32, n as f32);
+        let p = k / n;
+        let z2 = z * z;
+        let centre = (p + z2 / (2f32 * n)) / (1f32 + z2 / n);
+        let spread = z * libm::sqrtf(p * (1f32 - p) / n + z2 / (4f32 * n * n)) / (1f32 + z2 / n);
+
+        Self {
+            mean: p,
+            lo: (centre - spread).max(0f32),
+            hi: (centre + spread).min(1f32),
+        }
+    }
+}
+
+/// Draw a focal set from a BBA in proportion to its mass.
+fn draw<'a, S>(bba: &'a [(S, f32)], rng: &mut Rng) -> &'a S {
+    let total: f32 = bba.iter().map(|(_, m)| m).sum();
+    let mut u = rng.next_f32() * total;
+    for (s, m) in bba {
+        if u < *m {
+            return s;
+        }
+        u -= m;
+    }
+
+    // Rounding may leave `u` just beyond the last focal set with any mass.
+    &bba.iter()
+        .rev()
+        .find(|(_, m)| *m > 0f32)
+        .expect("Should have a BBA with non-zero mass.")
+        .0
+}
+
+/// Estimate `[bel(Q), pl(Q)]` of the Dempster combination of `bba` for each of the
+/// queries, from `trials` draws at the confidence given by `z`. Only the accepted
+/// trials inform the estimate, so heavy conflict demands more of them; should every
+/// trial be rejected, this is a `CombError::Conflict`.
+pub fn estimate<const Q: usize, S, B>(
+    bba: &[B],
+    queries: &[S; Q],
+    trials: usize,
+    z: f32,
+    rng: &mut Rng,
+) -> Result<[(Estimate, Estimate); Q], CombError>
+where
+    S: Set + Clone,
+    B: AsRef<[(S, f32)]>,
+{
+    assert!(!bba.is_empty(), "Called combination on an empty BBA?");
+
+    let mut accepted = 0usize;
+    let mut hits = [(0usize, 0usize); Q];
+    for _ in 0..trials {
+        let mut iter = bba.iter().map(|e| draw(e.as_ref(), rng));
+        let init = iter.next().expect("Non-empty.").clone();
+        let x = iter.fold(init, |acc, e| S::cap(&acc, e));
+        if x == S::EMPTY {
+            continue;
+        }
+
+        accepted += 1;
+        for (q, (b, p)) in queries.iter().zip(hits.iter_mut()) {
+            *b += x.is_subset(q) as usize;
+            *p += (S::cap(&x, q) != S::EMPTY) as usize;
+        }
+    }
+
+    if accepted == 0 {
+        return Err(CombError::Conflict);
+    }
+
+    Ok(hits.map(|(b, p)| {
+        (
+            Estimate::wilson(b, accepted, z),
+            Estimate::wilson(p, accepted, z),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::KX;
+    use crate::comb::Dempster;
+    use crate::dst::tests::abc::*;
+    use crate::dst::{bel, comb_approx, pl};
+
+    const M1: [(usize, f32); 3] = [(A, 0.5), (A | B, 0.3), (A | B | C, 0.2)];
+    const M2: [(usize, f32); 3] = [(B, 0.4), (B | C, 0.4), (A | B | C, 0.2)];
+    const M3: [(usize, f32); 2] = [(A | C, 0.7), (A | B | C, 0.3)];
+
+    #[test]
+    fn test_mc_dempster() {
+        let queries = [A, B, A | B, B | C];
+        let mut rng = Rng::seed(7);
+        let est = estimate(&[&M1[..], &M2, &M3], &queries, 20000, 3.0, &mut rng).unwrap();
+
+        let exact =
+            comb_approx::<8, usize, f32, KX, Dempster>([M1.to_vec(), M2.to_vec(), M3.to_vec()])
+                .unwrap();
+        for (q, (b, p)) in queries.iter().zip(est) {
+            let (b_q, p_q) = (bel(&exact, q), pl(&exact, q));
+            assert!(b.lo <= b_q && b_q <= b.hi, "bel({q}): {b_q} not in {b:?}");
+            assert!(p.lo <= p_q && p_q <= p.hi, "pl({q}): {p_q} not in {p:?}");
+            assert!(b.hi - b.lo < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_mc_reproducible() {
+        let run = |seed| estimate(&[M1, M2], &[A], 500, 1.96, &mut Rng::seed(seed)).unwrap();
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+    }
+
+    #[test]
+    fn test_mc_conflict() {
+        let m1 = [(A, 1.0f32)];
+        let m2 = [(B, 1.0f32)];
+        let err = estimate(&[m1, m2], &[A], 100, 1.96, &mut Rng::seed(0)).unwrap_err();
+        assert_eq!(err, CombError::Conflict);
+    }
+}
@@ -0,0 +1,168 @@
+//! Possibility theory via consonant belief functions.
+//!
+//! A possibility distribution `\pi` over a bitset frame, where `\pi_i` is the
+//! possibility of `S::singleton(i)`, is the contour function of a consonant BBA: one
+//! whose focal sets are nested. Under that correspondence the possibility and
+//! necessity measures are exactly `pl` and `bel`.
+use crate::builder::consonant;
+use crate::comb::{CombError, CombRule};
+use crate::dst::pl;
+use crate::set::Bitset;
+
+/// Build the consonant BBA of a normalized possibility distribution; the focal sets
+/// are its `\alpha`-cuts, `m(A_k) = \pi_{(k)} - \pi_{(k+1)}`.
+pub fn to_bba<const K: usize, S: Bitset + Clone>(pi: &[f32; K]) -> [(S, f32); K] {
+    assert!(pi.iter().all(|x| (0f32..=1f32).contains(x)));
+    assert!(pi.contains(&1f32), "Should have a fully possible element.");
+    consonant(pi, |_, d| d)
+}
+
+/// Compute the contour function of a BBA, `\pi_i = pl(\{\theta_i\})`; this is its
+/// possibility distribution if the BBA is consonant.
+pub fn from_bba<'a, const K: usize, S>(
+    bba: impl IntoIterator<Item = &'a (S, f32)> + Clone,
+) -> [f32; K]
+where
+    S: Bitset + 'a,
+{
+    core::array::from_fn(|i| pl(bba.clone(), &S::singleton(i)))
+}
+
+/// Compute whether the focal sets of a BBA are nested.
+pub fn is_consonant<'a, S, I>(bba: I) -> bool
+where
+    S: Bitset + 'a,
+    I: IntoIterator<Item = &'a (S, f32)> + Clone,
+{
+    let focal = |bba: I| bba.into_iter().filter(|(_, m)| *m > 0f32);
+    focal(bba.clone())
+        .all(|(a, _)| focal(bba.clone()).all(|(b, _)| a.is_subset(b) || b.is_subset(a)))
+}
+
+/// Compute the possibility of `Q`: `\Pi(Q) = \max_{\theta \in Q} \pi(\theta)`.
+pub fn possibility<const K: usize, S: Bitset>(pi: &[f32; K], q: &S) -> f32 {
+    pi.iter()
+        .enumerate()
+        .filter(|(i, _)| S::singleton(*i).is_subset(q))
+        .fold(0f32, |acc, (_, x)| f32::max(acc, *x))
+}
+
+/// Compute the necessity of `Q`: `N(Q) = 1 - \Pi(\neg Q)`.
+pub fn necessity<const K: usize, S: Bitset>(pi: &[f32; K], q: &S) -> f32 {
+    1f32 - pi
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !S::singleton(*i).is_subset(q))
+        .fold(0f32, |acc, (_, x)| f32::max(acc, *x))
+}
+
+/// The normalized min-based rule of possibility theory: the contour functions are
+/// combined via `\min`, renormalized by their height, and returned as a consonant BBA.
+pub struct MinPossibility;
+
+impl<S> CombRule<S, f32> for MinPossibility
+where
+    S: Bitset + Clone,
+{
+    fn comb<const N: usize, const M: usize>(
+        a: &[Option<(S, f32)>; N],
+        b: &[Option<(S, f32)>; M],
+    ) -> Result<impl Iterator<Item = (S, f32)>, CombError> {
+        let contour = |bba: &[Option<(S, f32)>], s: &S| -> f32 {
+            bba.iter()
+                .flatten()
+                .filter(|(p, _)| S::cap(p, s) != S::EMPTY)
+                .map(|(_, m)| m)
+                .sum()
+        };
+        let pi = move |i: usize| {
+            let s = S::singleton(i);
+            f32::min(contour(a, &s), contour(b, &s))
+        };
+
+        // Walk the distinct, non-zero levels of \pi in descending order rather than
+        // buffering them.
+        let below = move |l: f32| {
+            (0..S::BITS)
+                .map(pi)
+                .filter(|x| *x > 0f32 && *x < l)
+                .reduce(f32::max)
+        };
+        let h = below(f32::INFINITY).ok_or(CombError::Conflict)?;
+
+        // Each level's \alpha-cut is a focal set; m(C_k) = (\alpha_k - \alpha_{k+1}) / h.
+        Ok(
+            core::iter::successors(Some(h), move |l| below(*l)).map(move |l| {
+                let cut = (0..S::BITS)
+                    .filter(|i| pi(*i) >= l)
+                    .fold(S::EMPTY, |acc, i| S::cup(&acc, &S::singleton(i)));
+                (cut, (l - below(l).unwrap_or(0f32)) / h)
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dst::bel;
+
+    const TOL: f32 = 0.001;
+    const PI: [f32; 4] = [0.3, 1.0, 0.7, 0.0];
+
+    #[test]
+    fn test_round_trip() {
+        let bba: [(usize, f32); 4] = to_bba(&PI);
+        assert!(is_consonant(&bba));
+        let pi: [f32; 4] = from_bba(&bba);
+        for (l, r) in pi.iter().zip(PI) {
+            assert!((l - r).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_measures_match() {
+        let bba: [(usize, f32); 4] = to_bba(&PI);
+        for q in 0usize..16 {
+            assert!((possibility(&PI, &q) - pl(&bba, &q)).abs() < TOL);
+            assert!((necessity(&PI, &q) - bel(&bba, &q)).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_is_consonant() {
+        let not_nested = [(0b011usize, 0.5f32), (0b110, 0.5f32)];
+        assert!(!is_consonant(&not_nested));
+    }
+
+    #[test]
+    fn test_min() {
+        let a: [(usize, f32); 4] = to_bba(&[1.0, 0.6, 0.2, 0.0]);
+        let b: [(usize, f32); 4] = to_bba(&[0.5, 1.0, 0.4, 0.0]);
+        let (a, b) = (a.map(Some), b.map(Some));
+
+        let out: [Option<(usize, f32)>; 4] = {
+            let mut iter = MinPossibility::comb(&a, &b).unwrap();
+            core::array::from_fn(|_| iter.next())
+        };
+        let out: [(usize, f32); 4] = out.map(|x| x.unwrap_or((0, 0f32)));
+
+        // \min = [0.5, 0.6, 0.2, 0], with height 0.6.
+        let pi: [f32; 4] = from_bba(&out);
+        let expected = [0.5f32 / 0.6, 1.0, 0.2 / 0.6, 0.0];
+        for (l, r) in pi.iter().zip(expected) {
+            assert!((l - r).abs() < TOL);
+        }
+        assert!(is_consonant(&out));
+    }
+
+    #[test]
+    fn test_min_conflict() {
+        let a = [Some((0b01usize, 1.0f32))];
+        let b = [Some((0b10usize, 1.0f32))];
+        assert!(matches!(
+            MinPossibility::comb(&a, &b),
+            Err(CombError::Conflict)
+        ));
+    }
+}
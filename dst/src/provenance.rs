@@ -0,0 +1,210 @@
+//! Tracking which sources contributed to each focal element.
+//!
+//! A `Tracked` mass stands in for `f32` throughout `comb_approx`: alongside the mass,
+//! it carries the bitmask of the sources behind it and the share of the mass owed to
+//! each of them. Each term `m_1(X) m_2(Y)` of a combination is credited to the side
+//! that constrained `X \cap Y`: should `Y \subset X`, it's all `Y`'s, should
+//! `X \subset Y`, it's all `X`'s, and otherwise it's split evenly. A source that
+//! only ever contributes the frame, then, is credited with nothing beyond the
+//! mass left on the frame itself.
+//!
+//! The crediting is `Tracked`'s `Mass::meet`, which `Dempster` and `PCR5` defer to;
+//! `KX` rescales and `Summarize` merges the shares alongside the mass, so the
+//! provenance survives approximation.
+use crate::approx::Mass;
+use crate::set::Set;
+use core::ops::{AddAssign, MulAssign};
+
+/// A mass tracked across `P` sources, `P <= 64`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tracked<const P: usize> {
+    /// The mass.
+    pub mass: f32,
+    /// Bit `i` is set should source `i` have contributed.
+    pub sources: u64,
+    /// The mass owed to each source; these sum to `mass`.
+    pub share: [f32; P],
+}
+
+impl<const P: usize> Tracked<P> {
+    /// Construct the mass as provided wholly by source `i`.
+    pub fn source(i: usize, mass: f32) -> Self {
+        assert!(P <= 64 && i < P);
+        let mut share = [0f32; P];
+        share[i] = mass;
+        Self {
+            mass,
+            sources: 1 << i,
+            share,
+        }
+    }
+
+    /// Compute the fraction of the mass owed to source `i`.
+    pub fn fraction(&self, i: usize) -> f32 {
+        if self.mass > 0f32 {
+            self.share[i] / self.mass
+        } else {
+            0f32
+        }
+    }
+}
+
+impl<const P: usize> From<u8> for Tracked<P> {
+    fn from(mass: u8) -> Self {
+        // Mass that no source has claimed, e.g. the padding of `comb_approx`.
+        Self {
+            mass: mass.into(),
+            sources: 0u64,
+            share: [0f32; P],
+        }
+    }
+}
+
+impl<const P: usize> AddAssign for Tracked<P> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.mass += rhs.mass;
+        self.sources |= rhs.sources;
+        self.share
+            .iter_mut()
+            .zip(rhs.share)
+            .for_each(|(a, b)| *a += b);
+    }
+}
+
+impl<const P: usize> MulAssign<f32> for Tracked<P> {
+    fn mul_assign(&mut self, rhs: f32) {
+        self.mass *= rhs;
+        self.share.iter_mut().for_each(|a| *a *= rhs);
+    }
+}
+
+impl<const P: usize> Mass for Tracked<P> {
+    fn mass(&self) -> f32 {
+        self.mass
+    }
+
+    // Credited to the side that constrained `X \cap Y`.
+    fn meet<S: Set>(x: &S, a: &Self, y: &S, b: &Self) -> Self {
+        let (wa, wb) = match (y.is_subset(x), x.is_subset(y)) {
+            (true, false) => (0f32, 1f32),
+            (false, true) => (1f32, 0f32),
+            _ => (0.5f32, 0.5f32),
+        };
+
+        let mass = a.mass * b.mass;
+        let mut sources = 0u64;
+        if wa > 0f32 {
+            sources |= a.sources;
+        }
+        if wb > 0f32 {
+            sources |= b.sources;
+        }
+
+        Self {
+            mass,
+            sources,
+            share: core::array::from_fn(|i| mass * (wa * a.fraction(i) + wb * b.fraction(i))),
+        }
+    }
+}
+
+/// Attribute the BBA of source `i` to it.
+pub fn track<const P: usize, S>(
+    i: usize,
+    bba: impl IntoIterator<Item = (S, f32)>,
+) -> impl Iterator<Item = (S, Tracked<P>)> {
+    bba.into_iter()
+        .map(move |(s, m)| (s, Tracked::source(i, m)))
+}
+
+/// Drop the provenance of a BBA.
+pub fn untrack<const N: usize, const P: usize, S>(bba: [(S, Tracked<P>); N]) -> [(S, f32); N] {
+    bba.map(|(s, t)| (s, t.mass))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::{Summarize, KX};
+    use crate::comb::{CombRule, Dempster, PCR5};
+    use crate::dst::comb_approx;
+    use crate::dst::tests::abc::*;
+
+    const TOL: f32 = 0.001;
+
+    const M0: [(usize, f32); 2] = [(A, 0.8), (FRAME, 0.2)];
+    const M1: [(usize, f32); 1] = [(FRAME, 1.0)];
+    const M2: [(usize, f32); 2] = [(A | B, 0.6), (FRAME, 0.4)];
+
+    fn find<const N: usize>(bba: &[(usize, Tracked<3>); N], q: usize) -> Tracked<3> {
+        bba.iter().find(|(s, _)| *s == q).unwrap().1
+    }
+
+    #[test]
+    fn test_provenance_dempster() {
+        let tracked = comb_approx::<4, usize, Tracked<3>, KX, Dempster>([
+            track(0, M0).collect::<Vec<_>>(),
+            track(1, M1).collect(),
+            track(2, M2).collect(),
+        ])
+        .unwrap();
+
+        // The masses are untouched by tracking.
+        let plain =
+            comb_approx::<4, usize, f32, KX, Dempster>([&M0[..], &M1, &M2].map(|x| x.to_vec()))
+                .unwrap();
+        for (s, m) in untrack(tracked) {
+            let expected: f32 = plain.iter().filter(|x| x.0 == s).map(|x| x.1).sum();
+            assert!((m - expected).abs() < TOL);
+        }
+
+        // `A` is source 0's alone, and `A | B` is source 2's alone; the vacuous source
+        // 1 only shares in the frame.
+        let a = find(&tracked, A);
+        assert_eq!(a.sources, 0b001);
+        assert!((a.fraction(0) - 1f32).abs() < TOL);
+        let ab = find(&tracked, A | B);
+        assert_eq!(ab.sources, 0b100);
+        let frame = find(&tracked, FRAME);
+        assert_eq!(frame.sources, 0b111);
+        for (_, t) in tracked.iter() {
+            assert!((t.share.iter().sum::<f32>() - t.mass).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_provenance_summarize() {
+        let m3 = [(C, 0.3f32), (FRAME, 0.7f32)];
+        let tracked = comb_approx::<2, usize, Tracked<3>, Summarize, Dempster>([
+            track(0, M0).collect::<Vec<_>>(),
+            track(2, m3).collect(),
+        ])
+        .unwrap();
+
+        // `A` and `C` (in conflict) keep their sources through the merge, and the
+        // resulting shares still sum to the mass.
+        let merged = tracked
+            .iter()
+            .find(|(s, _)| *s & A != 0 && *s != A)
+            .unwrap()
+            .1;
+        assert_eq!(merged.sources & 0b101, 0b101);
+        let total: f32 = tracked.iter().map(|(_, t)| t.mass).sum();
+        assert!((total - 1f32).abs() < TOL);
+        for (_, t) in tracked.iter() {
+            assert!((t.share.iter().sum::<f32>() - t.mass).abs() < TOL);
+        }
+    }
+
+    #[test]
+    fn test_provenance_pcr5() {
+        let m1 = [Some((A, Tracked::<3>::source(0, 1.0))), None];
+        let m2 = [Some((B, Tracked::<3>::source(1, 1.0))), None];
+
+        // Total conflict: each side gets back its own half.
+        for (s, t) in <PCR5>::comb(&m1, &m2).unwrap() {
+            assert!((t.mass - 0.5f32).abs() < TOL);
+            assert_eq!(t.sources, if s == A { 0b01 } else { 0b10 });
+        }
+    }
+}
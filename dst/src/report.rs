@@ -0,0 +1,86 @@
+//! Summarizing a fused BBA over a bitset frame, one singleton at a time.
+use crate::dst::{bel, betp, pl};
+use crate::set::Bitset;
+
+/// The summary of a single hypothesis `\{\theta_i\}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row<S> {
+    /// The singleton.
+    pub singleton: S,
+    /// The belief, `bel(\{\theta_i\})`.
+    pub bel: f32,
+    /// The plausibility, `pl(\{\theta_i\})`.
+    pub pl: f32,
+    /// The pignistic probability, `BetP(\{\theta_i\})`.
+    pub betp: f32,
+    /// The ignorance remaining about the hypothesis, `pl - bel`.
+    pub width: f32,
+}
+
+/// Every singleton of a frame of `K` singletons, ranked by pignistic probability.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report<const K: usize, S> {
+    /// The rows, in order of decreasing `betp`.
+    pub rows: [Row<S>; K],
+    /// Whether the belief intervals of the two leading hypotheses overlap; i.e., the
+    /// runner-up remains as plausible as the leader is believed.
+    pub ambiguous: bool,
+}
+
+/// Construct the `Report` of a BBA over the frame of singletons `0..K`.
+pub fn report<const K: usize, S>(bba: &[(S, f32)]) -> Report<K, S>
+where
+    S: Bitset,
+{
+    assert!(K <= S::BITS);
+    let mut rows: [Row<S>; K] = core::array::from_fn(|i| {
+        let singleton = S::singleton(i);
+        let (bel, pl) = (bel(bba, &singleton), pl(bba, &singleton));
+        Row {
+            betp: betp(bba, &singleton),
+            singleton,
+            bel,
+            pl,
+            width: pl - bel,
+        }
+    });
+    rows.sort_unstable_by(|a, b| b.betp.total_cmp(&a.betp));
+
+    let ambiguous = match &rows[..] {
+        [first, second, ..] => second.pl >= first.bel,
+        _ => false,
+    };
+
+    Report { rows, ambiguous }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dst::tests::traffic_light::*;
+
+    const TOL: f32 = 0.001;
+
+    #[test]
+    fn test_report() {
+        let r = report::<3, usize>(TRAFFIC_BBA);
+
+        let order: [usize; 3] = core::array::from_fn(|i| r.rows[i].singleton);
+        assert_eq!(order, [RED, YELLOW, GREEN]);
+        assert!((r.rows[0].bel - 0.35f32).abs() < TOL);
+        assert!((r.rows[0].pl - 0.56f32).abs() < TOL);
+        assert!((r.rows[0].betp - 0.4383f32).abs() < TOL);
+        assert!((r.rows[0].width - 0.21f32).abs() < TOL);
+        // pl(yellow) = 0.45 exceeds bel(red) = 0.35.
+        assert!(r.ambiguous);
+    }
+
+    #[test]
+    fn test_report_decisive() {
+        let bba = [(RED, 0.8f32), (RED | YELLOW | GREEN, 0.2f32)];
+        let r = report::<3, usize>(&bba);
+        assert_eq!(r.rows[0].singleton, RED);
+        assert!((r.rows[1].pl - 0.2f32).abs() < TOL);
+        assert!(!r.ambiguous);
+    }
+}
@@ -0,0 +1,56 @@
+//! Serde helpers for const-generic arrays.
+//!
+//! `serde` only implements its traits for arrays of up to 32 elements, which rules
+//! out BBAs such as `[Option<(S, T)>; N]` for a generic `N`. These are meant to be
+//! used as `#[serde(with = "dst::serde_array")]` and never allocate.
+use core::{fmt, marker::PhantomData};
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+
+/// Serialize an `[T; N]` as a tuple of `N` elements.
+pub fn serialize<S, T, const N: usize>(arr: &[T; N], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    let mut tup = serializer.serialize_tuple(N)?;
+    for elem in arr {
+        tup.serialize_element(elem)?;
+    }
+    tup.end()
+}
+
+/// Deserialize an `[T; N]` from a tuple of exactly `N` elements.
+pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct ArrVisitor<T, const N: usize>(PhantomData<T>);
+
+    impl<'de, T, const N: usize> Visitor<'de> for ArrVisitor<T, N>
+    where
+        T: Deserialize<'de>,
+    {
+        type Value = [T; N];
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "an array of length {}", N)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            // There's no fallible `from_fn` on stable; stage through `Option`s instead.
+            let mut buf: [Option<T>; N] = core::array::from_fn(|_| None);
+            for (i, mem) in buf.iter_mut().enumerate() {
+                *mem = Some(
+                    seq.next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?,
+                );
+            }
+
+            Ok(buf.map(|x| x.unwrap()))
+        }
+    }
+
+    deserializer.deserialize_tuple(N, ArrVisitor::<T, N>(PhantomData))
+}
@@ -1,6 +1,9 @@
 //! Set operations needed for a DST impl.
 
 /// A mathematical Set, but specifically those operations needed for a DST impl.
+///
+/// This is a lattice: `cap` and `cup` are the meet and join, `is_subset` is the
+/// order, and `EMPTY` is the bottom. A complement isn't required; see `Complement`.
 pub trait Set: PartialEq {
     /// Compute whether the LHS is a subset of the RHS.
     fn is_subset(&self, rhs: &Self) -> bool;
@@ -8,13 +11,28 @@ pub trait Set: PartialEq {
     fn cap(lhs: &Self, rhs: &Self) -> Self;
     /// Compute the union between the LHS and RHS.
     fn cup(lhs: &Self, rhs: &Self) -> Self;
-    /// Compute the NOT of some Set.
-    fn not(&self) -> Self;
 
     /// A representation of the Empty Set.
     const EMPTY: Self;
 }
 
+/// A `Set` with a complement, making the lattice a Boolean algebra.
+pub trait Complement: Set {
+    /// Compute the NOT of some Set.
+    fn not(&self) -> Self;
+}
+
+/// A `Set` over a finite frame, where each element of the frame is a bit.
+pub trait Bitset: Complement {
+    /// The number of elements within the frame.
+    const BITS: usize;
+
+    /// Compute the number of elements within the set.
+    fn card(&self) -> usize;
+    /// The singleton set of the `i`th element of the frame.
+    fn singleton(i: usize) -> Self;
+}
+
 impl Set for usize {
     fn is_subset(&self, rhs: &Self) -> bool {
         self & rhs == *self
@@ -28,11 +46,25 @@ impl Set for usize {
         lhs | rhs
     }
 
+    const EMPTY: Self = 0usize;
+}
+
+impl Complement for usize {
     fn not(&self) -> Self {
         !self
     }
+}
+
+impl Bitset for usize {
+    const BITS: usize = usize::BITS as usize;
+
+    fn card(&self) -> usize {
+        self.count_ones() as usize
+    }
 
-    const EMPTY: Self = 0usize;
+    fn singleton(i: usize) -> Self {
+        1 << i
+    }
 }
 
 impl<const N: usize> Set for [u8; N] {
@@ -56,19 +88,186 @@ impl<const N: usize> Set for [u8; N] {
         buf
     }
 
+    const EMPTY: Self = [0u8; N];
+}
+
+impl<const N: usize> Complement for [u8; N] {
     fn not(&self) -> Self {
         let mut buf = Self::EMPTY;
         buf.iter_mut().zip(self).for_each(|(i, x)| *i = !x);
         buf
     }
+}
 
-    const EMPTY: Self = [0u8; N];
+impl<const N: usize> Bitset for [u8; N] {
+    const BITS: usize = 8 * N;
+
+    fn card(&self) -> usize {
+        self.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    fn singleton(i: usize) -> Self {
+        let mut buf = Self::EMPTY;
+        buf[i / 8] = 1 << (i % 8);
+        buf
+    }
+}
+
+pub use hyper::HyperPowerSet;
+pub use interval::Interval;
+
+mod hyper {
+    use super::Set;
+
+    /// An element of the hyper-power set `D^\Theta` of a frame of `N` hypotheses under
+    /// the free DSm model: the closure of `\Theta` under `\cup` and `\cap`, where the
+    /// hypotheses may overlap and there's no complement.
+    ///
+    /// Each element is stored as the parts of the Venn diagram it covers; the part where
+    /// exactly the hypotheses within the mask `r` overlap is bit `r - 1`. With `N <= 6`
+    /// the `2^N - 1` parts fit within a `u64`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+    pub struct HyperPowerSet<const N: usize> {
+        parts: u64,
+    }
+
+    impl<const N: usize> HyperPowerSet<N> {
+        /// The number of parts of the Venn diagram.
+        const PARTS: usize = (1 << N) - 1;
+
+        /// The hypothesis `\theta_i`; every part where it takes part.
+        pub fn theta(i: usize) -> Self {
+            assert!(N <= 6 && i < N);
+            let parts = (1..=Self::PARTS)
+                .filter(|r| r & (1 << i) != 0)
+                .fold(0u64, |acc, r| acc | 1 << (r - 1));
+            Self { parts }
+        }
+
+        /// The total ignorance, `\theta_1 \cup ... \cup \theta_N`.
+        pub fn total() -> Self {
+            assert!(N <= 6);
+            Self {
+                parts: u64::MAX >> (64 - Self::PARTS),
+            }
+        }
+
+        /// The DSm cardinality: the number of parts of the Venn diagram covered.
+        pub fn card(&self) -> usize {
+            self.parts.count_ones() as usize
+        }
+
+        /// Every element of `D^\Theta`, `\emptyset` included; there's a Dedekind number
+        /// of them (2, 5, 19, 167), so this is limited to `N <= 4`.
+        pub fn elements() -> impl Iterator<Item = Self> {
+            assert!(N <= 4);
+            (0u64..1 << Self::PARTS)
+                .filter(|parts| Self::is_element(*parts))
+                .map(|parts| Self { parts })
+        }
+
+        /// Whether the parts are an element of `D^\Theta`. The elements are exactly those
+        /// sets of parts that are up-closed: should the overlap `r` be covered, so is any
+        /// overlap of more hypotheses.
+        fn is_element(parts: u64) -> bool {
+            N <= 6
+                && parts >> Self::PARTS == 0
+                && (1..=Self::PARTS)
+                    .filter(|r| parts & 1 << (r - 1) != 0)
+                    .all(|r| (0..N).all(|i| parts & 1 << ((r | 1 << i) - 1) != 0))
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de, const N: usize> serde::Deserialize<'de> for HyperPowerSet<N> {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            // Only the parts of an element of `D^\Theta`, as `elements` would produce.
+            let parts = u64::deserialize(deserializer)?;
+            if Self::is_element(parts) {
+                Ok(Self { parts })
+            } else {
+                Err(serde::de::Error::custom(
+                    "parts are not an element of the hyper-power set",
+                ))
+            }
+        }
+    }
+
+    impl<const N: usize> Set for HyperPowerSet<N> {
+        fn is_subset(&self, rhs: &Self) -> bool {
+            self.parts & rhs.parts == self.parts
+        }
+
+        fn cap(lhs: &Self, rhs: &Self) -> Self {
+            Self {
+                parts: lhs.parts & rhs.parts,
+            }
+        }
+
+        fn cup(lhs: &Self, rhs: &Self) -> Self {
+            Self {
+                parts: lhs.parts | rhs.parts,
+            }
+        }
+
+        const EMPTY: Self = Self { parts: 0u64 };
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        type D3 = HyperPowerSet<3>;
+
+        #[test]
+        fn test_hyper_dedekind() {
+            assert_eq!(HyperPowerSet::<1>::elements().count(), 2);
+            assert_eq!(HyperPowerSet::<2>::elements().count(), 5);
+            assert_eq!(D3::elements().count(), 19);
+            assert_eq!(HyperPowerSet::<4>::elements().count(), 167);
+        }
+
+        #[test]
+        fn test_hyper_overlap() {
+            let (a, b, c) = (D3::theta(0), D3::theta(1), D3::theta(2));
+            // Hypotheses overlap in the free model...
+            assert_ne!(D3::cap(&a, &b), D3::EMPTY);
+            assert_ne!(D3::cap(&D3::cap(&a, &b), &c), D3::EMPTY);
+            // ... but distributivity still holds.
+            assert_eq!(
+                D3::cap(&a, &D3::cup(&b, &c)),
+                D3::cup(&D3::cap(&a, &b), &D3::cap(&a, &c))
+            );
+            assert_eq!(D3::cup(&D3::cup(&a, &b), &c), D3::total());
+            assert!(D3::cap(&a, &b).is_subset(&a));
+            assert_eq!(a.card(), 4);
+            assert_eq!(D3::cap(&a, &b).card(), 2);
+        }
+
+        #[test]
+        fn test_hyper_closed() {
+            // Every meet and join of elements is an element.
+            let elems: [D3; 19] = {
+                let mut iter = D3::elements();
+                core::array::from_fn(|_| iter.next().unwrap())
+            };
+            for x in elems.iter() {
+                for y in elems.iter() {
+                    assert!(elems.contains(&D3::cap(x, y)));
+                    assert!(elems.contains(&D3::cup(x, y)));
+                }
+            }
+        }
+    }
 }
 
 mod interval {
     use super::Set;
     use core::cmp::Ord;
+    use core::hash::{Hash, Hasher};
 
+    /// An `N`-dimensional box, where each dimension is a closed interval.
     #[derive(Debug)]
     pub struct Interval<const N: usize, T> {
         // Consider the case where we compute the intersection and a certain dimension
@@ -77,6 +276,7 @@ mod interval {
     }
 
     impl<const N: usize, T> Interval<N, T> {
+        /// Construct an `Interval`; `None` signifies an empty dimension.
         pub fn build(buf: [Option<(T, T)>; N]) -> Self
         where
             T: PartialOrd,
@@ -111,6 +311,46 @@ mod interval {
         }
     }
 
+    impl<const N: usize, T> Eq for Interval<N, T> where T: Eq {}
+
+    impl<const N: usize, T> Hash for Interval<N, T>
+    where
+        T: Hash,
+    {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            // Equivalent to `PartialEq` above: dimension-wise over the `Option`s.
+            self.buf.hash(state);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<const N: usize, T> serde::Serialize for Interval<N, T>
+    where
+        T: serde::Serialize,
+    {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            crate::serde_array::serialize(&self.buf, serializer)
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de, const N: usize, T> serde::Deserialize<'de> for Interval<N, T>
+    where
+        T: PartialOrd + serde::Deserialize<'de>,
+    {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            // Uphold the invariant checked within `build`, but as an error.
+            let buf: [Option<(T, T)>; N] = crate::serde_array::deserialize(deserializer)?;
+            if buf.iter().flatten().all(|(lhs, rhs)| lhs <= rhs) {
+                Ok(Self { buf })
+            } else {
+                Err(serde::de::Error::custom(
+                    "interval lower bound exceeds upper bound",
+                ))
+            }
+        }
+    }
+
     impl<const N: usize, T> Set for Interval<N, T>
     where
         T: PartialOrd + Ord + Copy,
@@ -160,13 +400,6 @@ mod interval {
             Self::binop(lhs, rhs, f)
         }
 
-        fn not(&self) -> Self {
-            // TODO: The NOT of an `Interval` is two `Interval`s; a potential implementation
-            // is to utilize some flag to indicate that `subset`, `cup`, etc. should NOT their
-            // results?
-            unimplemented!();
-        }
-
         const EMPTY: Self = Self { buf: [None; N] };
     }
 
@@ -213,8 +446,9 @@ mod interval {
         fn test_interval_cup_disjoint() {
             let a = Interval::build([Some((0, 10))]);
             let b = Interval::build([Some((11, 20))]);
-            let c = Interval::cup(&a, &b); // A \cup B = (0, 10) U (11, 20).
-            assert_eq!(c, todo!());
+            // The join is the hull, `(0, 20)`, not `(0, 10) U (11, 20)`.
+            let c = Interval::cup(&a, &b);
+            assert_eq!(c, Interval::build([Some((0, 20))]));
         }
 
         #[test]
@@ -250,10 +484,5 @@ mod interval {
             let b = Interval::build([Some((0, 10))]);
             assert_eq!(Interval::cap(&Interval::EMPTY, &b), Interval::EMPTY); // EMPTY \cap B = EMPTY.
         }
-
-        #[test]
-        fn test_interval_not() {
-            todo!();
-        }
     }
 }
@@ -0,0 +1,435 @@
+//! A valuation-based system: belief functions over a network of variables,
+//! propagated over a join tree with the Shenoy-Shafer architecture.
+//!
+//! Each valuation is a BBA over the joint frame of a handful of variables, such as
+//! a sensor model relating a sensor's reading to an observation. Valuations are
+//! combined after a vacuous extension onto the union of their domains, and are
+//! marginalized by coarsening; both via the refinings within `frame`.
+//!
+//! The joint frame of a domain is a `usize` bitset over configurations, so every
+//! clique of the join tree must have at most `usize::BITS` configurations.
+use crate::approx::Approximation;
+use crate::comb::{CombError, CombRule};
+use crate::{dst, frame};
+use alloc::{vec, vec::Vec};
+use core::iter::Sum;
+use core::marker::PhantomData;
+use core::ops::{AddAssign, MulAssign, Sub};
+
+/// Errors that can occur when propagating over a join tree.
+#[derive(Debug, PartialEq)]
+pub enum PropagationError {
+    /// A clique has more configurations than fit within a `usize` bitset.
+    Clique,
+    /// Combining two valuations failed.
+    Comb(CombError),
+}
+
+impl From<CombError> for PropagationError {
+    fn from(e: CombError) -> Self {
+        PropagationError::Comb(e)
+    }
+}
+
+/// A BBA over the joint frame of the variables within `domain`.
+#[derive(Debug, Clone)]
+struct Valuation<const N: usize, T> {
+    domain: Vec<usize>, // Variable indices, ascending.
+    bba: [Option<(usize, T)>; N],
+}
+
+/// A network of variables and the valuations relating them.
+///
+/// The configuration `(x_0, x_1, ...)` of a domain `[v_0, v_1, ...]` is the bit
+/// `x_0 + n_0 * (x_1 + n_1 * (...))`, where `n_i` is the size of the frame of `v_i`.
+#[derive(Debug)]
+pub struct Network<const N: usize, T, A, C> {
+    frames: Vec<usize>,
+    valuations: Vec<Valuation<N, T>>,
+
+    phantom: PhantomData<(A, C)>,
+}
+
+impl<const N: usize, T, A, C> Default for Network<N, T, A, C> {
+    fn default() -> Self {
+        Self {
+            frames: Vec::new(),
+            valuations: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize, T, A, C> Network<N, T, A, C>
+where
+    T: From<u8> + AddAssign + MulAssign + Clone,
+    A: Approximation<usize, T>,
+    C: CombRule<usize, T>,
+{
+    /// Add a variable with a frame of `size` singletons, returning its index.
+    pub fn variable(&mut self, size: usize) -> usize {
+        assert!(size > 0 && size <= usize::BITS as usize);
+        self.frames.push(size);
+        self.frames.len() - 1
+    }
+
+    /// Compute the singleton of `domain` where each variable takes on `values`.
+    pub fn config(&self, domain: &[usize], values: &[usize]) -> usize {
+        assert!(domain.len() == values.len());
+        assert!(domain.iter().zip(values).all(|(v, x)| *x < self.frames[*v]));
+        1 << self.index(domain, |i| values[i])
+    }
+
+    /// Add a valuation over `domain`, which should be ascending.
+    pub fn valuation(&mut self, domain: &[usize], bba: [Option<(usize, T)>; N]) {
+        assert!(domain.windows(2).all(|w| w[0] < w[1]));
+        assert!(domain.iter().all(|v| *v < self.frames.len()));
+        assert!(self.configs(domain).is_some());
+        self.valuations.push(Valuation {
+            domain: domain.to_vec(),
+            bba,
+        });
+    }
+
+    /// The number of configurations of `domain`, should they fit within a bitset.
+    fn configs(&self, domain: &[usize]) -> Option<usize> {
+        domain
+            .iter()
+            .try_fold(1usize, |acc, v| acc.checked_mul(self.frames[*v]))
+            .filter(|n| *n <= usize::BITS as usize)
+    }
+
+    /// The index of a configuration of `domain`, given the value of its `i`th variable.
+    fn index(&self, domain: &[usize], value: impl Fn(usize) -> usize) -> usize {
+        domain
+            .iter()
+            .enumerate()
+            .rev()
+            .fold(0usize, |acc, (i, v)| acc * self.frames[*v] + value(i))
+    }
+
+    /// The refining from the configurations of `coarse` onto those of `fine`, which
+    /// should contain every variable of `coarse`.
+    fn refining(&self, coarse: &[usize], fine: &[usize]) -> Vec<(usize, usize)> {
+        let mut blocks: Vec<(usize, usize)> = (0..self.configs(coarse).unwrap())
+            .map(|c| (1 << c, 0usize))
+            .collect();
+
+        for f in 0..self.configs(fine).unwrap() {
+            // Decode the fine configuration, then re-encode its restriction to `coarse`.
+            let mut values = vec![0usize; fine.len()];
+            values.iter_mut().zip(fine).fold(f, |acc, (x, v)| {
+                *x = acc % self.frames[*v];
+                acc / self.frames[*v]
+            });
+
+            let c = self.index(coarse, |i| {
+                values[fine.iter().position(|v| *v == coarse[i]).unwrap()]
+            });
+            blocks[c].1 |= 1 << f;
+        }
+
+        blocks
+    }
+
+    /// The vacuous valuation over `domain`.
+    fn vacuous(&self, domain: &[usize]) -> Valuation<N, T> {
+        let frame = usize::MAX >> (usize::BITS as usize - self.configs(domain).unwrap());
+        let mut bba: [Option<(usize, T)>; N] = core::array::from_fn(|_| None);
+        bba[0] = Some((frame, 1u8.into()));
+        Valuation {
+            domain: domain.to_vec(),
+            bba,
+        }
+    }
+
+    /// Vacuously extend a valuation onto `domain`.
+    fn extend(&self, v: &Valuation<N, T>, domain: &[usize]) -> [Option<(usize, T)>; N] {
+        frame::refine_bba(&self.refining(&v.domain, domain), v.bba.clone())
+    }
+
+    /// Marginalize a valuation onto `domain`.
+    fn marginalize(&self, v: &Valuation<N, T>, domain: &[usize]) -> Valuation<N, T> {
+        Valuation {
+            domain: domain.to_vec(),
+            bba: frame::coarsen_bba(&self.refining(domain, &v.domain), v.bba.clone()),
+        }
+    }
+
+    /// Combine two valuations over the union of their domains.
+    fn combine(
+        &self,
+        a: &Valuation<N, T>,
+        b: &Valuation<N, T>,
+    ) -> Result<Valuation<N, T>, PropagationError> {
+        let domain = union(&a.domain, &b.domain);
+        self.configs(&domain).ok_or(PropagationError::Clique)?;
+
+        let bba = A::approx(C::comb(&self.extend(a, &domain), &self.extend(b, &domain))?);
+        Ok(Valuation { domain, bba })
+    }
+
+    /// Construct a join tree by variable elimination then pass messages over it.
+    pub fn propagate(&self) -> Result<JoinTree<'_, N, T, A, C>, PropagationError> {
+        enum Origin {
+            Input(usize),
+            Node(usize),
+        }
+
+        let mut pending: Vec<(Vec<usize>, Origin)> = self
+            .valuations
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.domain.clone(), Origin::Input(i)))
+            .collect();
+        let mut remaining: Vec<usize> = (0..self.frames.len()).collect();
+        let mut nodes: Vec<Node<N, T>> = Vec::new();
+
+        while !remaining.is_empty() {
+            // Eliminate the variable that produces the smallest clique.
+            let clique = |x: usize| {
+                pending
+                    .iter()
+                    .filter(|(d, _)| d.contains(&x))
+                    .fold(vec![x], |acc, (d, _)| union(&acc, d))
+            };
+            let (r_idx, x) = remaining
+                .iter()
+                .copied()
+                .enumerate()
+                .min_by_key(|(_, x)| self.configs(&clique(*x)).unwrap_or(usize::MAX))
+                .unwrap();
+            let domain = clique(x);
+            self.configs(&domain).ok_or(PropagationError::Clique)?;
+            remaining.swap_remove(r_idx);
+
+            // Absorb everything mentioning `x`, whether valuations or child messages.
+            let k = nodes.len();
+            let mut local = self.vacuous(&[x]);
+            let mut children = Vec::new();
+            for (_, origin) in pending.extract_if(.., |(d, _)| d.contains(&x)) {
+                match origin {
+                    Origin::Input(i) => local = self.combine(&local, &self.valuations[i])?,
+                    Origin::Node(j) => {
+                        nodes[j].parent = Some(k);
+                        children.push(j);
+                    }
+                }
+            }
+
+            let local = self.combine(&local, &self.vacuous(&domain))?;
+            let residual: Vec<usize> = domain.iter().copied().filter(|v| *v != x).collect();
+            if !residual.is_empty() {
+                pending.push((residual, Origin::Node(k)));
+            }
+
+            nodes.push(Node {
+                local,
+                parent: None,
+                children,
+                up: None,
+                down: None,
+            });
+        }
+
+        let mut tree = JoinTree {
+            network: self,
+            nodes,
+        };
+        tree.pass()?;
+        Ok(tree)
+    }
+}
+
+/// A clique of the join tree, alongside the messages along the edge to its parent.
+#[derive(Debug)]
+struct Node<const N: usize, T> {
+    local: Valuation<N, T>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    up: Option<Valuation<N, T>>,   // To the parent.
+    down: Option<Valuation<N, T>>, // From the parent.
+}
+
+/// A join tree after Shenoy-Shafer message passing; every marginal is available.
+#[derive(Debug)]
+pub struct JoinTree<'a, const N: usize, T, A, C> {
+    network: &'a Network<N, T, A, C>,
+    nodes: Vec<Node<N, T>>,
+}
+
+impl<const N: usize, T, A, C> JoinTree<'_, N, T, A, C>
+where
+    T: From<u8> + AddAssign + MulAssign + Clone,
+    A: Approximation<usize, T>,
+    C: CombRule<usize, T>,
+{
+    /// Combine the local valuation of node `k` with every message into it, save
+    /// for the one from `except`.
+    fn absorb(&self, k: usize, except: Option<usize>) -> Result<Valuation<N, T>, PropagationError> {
+        let node = &self.nodes[k];
+        let from_children = node
+            .children
+            .iter()
+            .filter(|c| Some(**c) != except)
+            .map(|c| self.nodes[*c].up.as_ref().unwrap());
+        let from_parent = node.down.as_ref().filter(|_| except != node.parent);
+
+        from_children
+            .chain(from_parent)
+            .try_fold(node.local.clone(), |acc, m| self.network.combine(&acc, m))
+    }
+
+    /// Pass messages inwards then outwards; children always precede their parent.
+    fn pass(&mut self) -> Result<(), PropagationError> {
+        for k in 0..self.nodes.len() {
+            if let Some(p) = self.nodes[k].parent {
+                let sep = intersection(&self.nodes[k].local.domain, &self.nodes[p].local.domain);
+                let m = self.network.marginalize(&self.absorb(k, Some(p))?, &sep);
+                self.nodes[k].up = Some(m);
+            }
+        }
+
+        for k in (0..self.nodes.len()).rev() {
+            if let Some(p) = self.nodes[k].parent {
+                let sep = intersection(&self.nodes[k].local.domain, &self.nodes[p].local.domain);
+                let m = self.network.marginalize(&self.absorb(p, Some(k))?, &sep);
+                self.nodes[k].down = Some(m);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compute the marginal BBA of variable `x`, where bit `i` is its `i`th value.
+    pub fn marginal(&self, x: usize) -> Result<[Option<(usize, T)>; N], PropagationError> {
+        let k = self
+            .nodes
+            .iter()
+            .position(|n| n.local.domain.contains(&x))
+            .expect("Should have a clique for every variable.");
+        Ok(self.network.marginalize(&self.absorb(k, None)?, &[x]).bba)
+    }
+
+    /// Compute the belief of `q` for variable `x`.
+    pub fn bel(&self, x: usize, q: &usize) -> Result<T, PropagationError>
+    where
+        T: for<'b> Sum<&'b T>,
+    {
+        let bba: Vec<(usize, T)> = self.marginal(x)?.into_iter().flatten().collect();
+        Ok(dst::bel(&bba, q))
+    }
+
+    /// Compute the plausibility of `q` for variable `x`.
+    pub fn pl(&self, x: usize, q: &usize) -> Result<T, PropagationError>
+    where
+        T: for<'b> Sum<&'b T> + Sub<Output = T>,
+    {
+        let bba: Vec<(usize, T)> = self.marginal(x)?.into_iter().flatten().collect();
+        Ok(dst::pl(&bba, q))
+    }
+}
+
+/// Merge two ascending lists of variables.
+fn union(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut r: Vec<usize> = a.iter().chain(b).copied().collect();
+    r.sort_unstable();
+    r.dedup();
+    r
+}
+
+/// Intersect two ascending lists of variables.
+fn intersection(a: &[usize], b: &[usize]) -> Vec<usize> {
+    a.iter().copied().filter(|v| b.contains(v)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::KX;
+    use crate::comb::Dempster;
+
+    const TOL: f32 = 0.001;
+    type Net = Network<4, f32, KX, Dempster>;
+
+    #[test]
+    fn test_sensor_model() {
+        // target: {car, truck, person}; observation: {big, small}.
+        let mut net = Net::default();
+        let target = net.variable(3);
+        let obs = net.variable(2);
+        let (car, truck, person) = (0b001usize, 0b010usize, 0b100usize);
+
+        // Vehicles look big, people look small.
+        let d = [target, obs];
+        let rel = net.config(&d, &[0, 0]) | net.config(&d, &[1, 0]) | net.config(&d, &[2, 1]);
+        net.valuation(
+            &d,
+            [Some((rel, 0.9f32)), Some((0b111111, 0.1f32)), None, None],
+        );
+        net.valuation(
+            &[obs],
+            [Some((0b01, 0.8f32)), Some((0b11, 0.2f32)), None, None],
+        );
+
+        let tree = net.propagate().unwrap();
+        assert!((tree.bel(target, &(car | truck)).unwrap() - 0.72f32).abs() < TOL);
+        assert!((tree.pl(target, &person).unwrap() - 0.28f32).abs() < TOL);
+        assert!((tree.bel(obs, &0b01).unwrap() - 0.8f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_chain() {
+        // x_0 == x_1 for certain, x_1 == x_2 with 0.9; evidence that x_0 is 1.
+        let mut net = Net::default();
+        let x: [usize; 3] = core::array::from_fn(|_| net.variable(2));
+        let eq = |net: &Net, a: usize, b: usize| {
+            net.config(&[a, b], &[0, 0]) | net.config(&[a, b], &[1, 1])
+        };
+
+        let e01 = eq(&net, x[0], x[1]);
+        let e12 = eq(&net, x[1], x[2]);
+        net.valuation(&[x[0], x[1]], [Some((e01, 1.0f32)), None, None, None]);
+        net.valuation(
+            &[x[1], x[2]],
+            [Some((e12, 0.9f32)), Some((0b1111, 0.1f32)), None, None],
+        );
+        net.valuation(
+            &[x[0]],
+            [Some((0b10, 0.7f32)), Some((0b11, 0.3f32)), None, None],
+        );
+
+        let tree = net.propagate().unwrap();
+        assert!((tree.bel(x[1], &0b10).unwrap() - 0.7f32).abs() < TOL);
+        assert!((tree.bel(x[2], &0b10).unwrap() - 0.63f32).abs() < TOL);
+        assert!((tree.pl(x[2], &0b01).unwrap() - 0.37f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_disconnected() {
+        // A variable without any valuation is vacuous.
+        let mut net = Net::default();
+        let a = net.variable(2);
+        let b = net.variable(3);
+        net.valuation(&[a], [Some((0b01, 1.0f32)), None, None, None]);
+
+        let tree = net.propagate().unwrap();
+        assert!((tree.bel(a, &0b01).unwrap() - 1.0f32).abs() < TOL);
+        assert!(tree.bel(b, &0b011).unwrap().abs() < TOL);
+        assert!((tree.pl(b, &0b001).unwrap() - 1.0f32).abs() < TOL);
+    }
+
+    #[test]
+    fn test_clique_too_large() {
+        let mut net = Net::default();
+        let a = net.variable(8);
+        let b = net.variable(8);
+        let c = net.variable(8);
+        net.valuation(&[a, b], [Some((usize::MAX, 1.0f32)), None, None, None]);
+        net.valuation(&[b, c], [Some((usize::MAX, 1.0f32)), None, None, None]);
+        net.valuation(&[a, c], [Some((usize::MAX, 1.0f32)), None, None, None]);
+
+        // The cycle forces a clique over all three: 512 configurations.
+        assert!(matches!(net.propagate(), Err(PropagationError::Clique)));
+    }
+}
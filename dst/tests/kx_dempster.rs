@@ -13,7 +13,7 @@ fn films_high_conflict() {
         [(FILM_Z, 0.99f32), (FILM_Y, 0.01f32)],
     ];
 
-    let bba = comb_approx::<2, usize, f32, KX, Dempster>(FILMS_HIGH_CONFLICT);
+    let bba = comb_approx::<2, usize, f32, KX, Dempster>(FILMS_HIGH_CONFLICT).unwrap();
 
     const EPS: f32 = 0.001f32;
 
@@ -0,0 +1,74 @@
+#![cfg(feature = "serde")]
+use dst::approx::KX;
+use dst::comb::Dempster;
+use dst::dst::comb_approx;
+use dst::set::Interval;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Evidence<const N: usize> {
+    #[serde(with = "dst::serde_array")]
+    bba: [(usize, f32); N],
+}
+
+#[test]
+fn bba_round_trip() {
+    const BBAS: [[(usize, f32); 2]; 2] = [
+        [(0b011, 0.6f32), (0b111, 0.4f32)],
+        [(0b110, 0.7f32), (0b111, 0.3f32)],
+    ];
+
+    // Wider than `serde`'s own array impls to exercise the helper.
+    let evidence = Evidence::<40> {
+        bba: comb_approx::<40, usize, f32, KX, Dempster>(BBAS).unwrap(),
+    };
+
+    let json = serde_json::to_string(&evidence).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Evidence<40>>(&json).unwrap(),
+        evidence
+    );
+}
+
+#[test]
+fn bba_wrong_length() {
+    let json = serde_json::to_string(&Evidence::<2> {
+        bba: [(0b01, 0.5f32), (0b10, 0.5f32)],
+    })
+    .unwrap();
+    assert!(serde_json::from_str::<Evidence<3>>(&json).is_err());
+}
+
+#[test]
+fn bitset_round_trip() {
+    let bba: [Option<([u8; 4], f32)>; 2] = [Some(([0xF0, 0, 0, 1], 0.25f32)), None];
+    let json = serde_json::to_string(&bba).unwrap();
+    assert_eq!(
+        serde_json::from_str::<[Option<([u8; 4], f32)>; 2]>(&json).unwrap(),
+        bba
+    );
+}
+
+#[test]
+fn interval_round_trip() {
+    let a = Interval::build([Some((0i32, 10i32)), None]);
+    let json = serde_json::to_string(&a).unwrap();
+    assert_eq!(serde_json::from_str::<Interval<2, i32>>(&json).unwrap(), a);
+
+    // A reversed interval is rejected rather than silently accepted.
+    assert!(serde_json::from_str::<Interval<2, i32>>("[[10,0],null]").is_err());
+}
+
+#[test]
+fn hyper_power_set_round_trip() {
+    use dst::set::{HyperPowerSet, Set};
+    type D3 = HyperPowerSet<3>;
+    let a = D3::cap(&D3::theta(0), &D3::theta(1));
+    let json = serde_json::to_string(&a).unwrap();
+    assert_eq!(serde_json::from_str::<D3>(&json).unwrap(), a);
+
+    // Parts beyond the Venn diagram, or that aren't up-closed (`\theta_0` alone, without
+    // its overlaps), are rejected.
+    assert!(serde_json::from_str::<D3>("128").is_err());
+    assert!(serde_json::from_str::<D3>("1").is_err());
+}
@@ -1,6 +1,7 @@
 //! An alpha-beta filter from Basic Radar Tracking by Budge.
 use crate::Filter;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct AlphaBeta<P> {
     alpha: P,
     beta: P,
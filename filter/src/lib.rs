@@ -2,5 +2,5 @@ mod alpha_beta;
 
 trait Filter<const N: usize, P> {
     fn predict(update_period: P, X_s: &[P; N]) -> [P; N];
-    fn update(update_period: P, X_p: &[T; N], y: &[T; N]) -> [T; N];
+    fn update(update_period: P, X_p: &[P; N], y: &[P; N]) -> [P; N];
 }
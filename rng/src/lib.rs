@@ -0,0 +1,33 @@
+//! A small, seedable random number generator, shared by the Monte Carlo routines of
+//! `dst` and the randomized forecasters of `agg-exp`.
+#![no_std]
+#![warn(missing_docs)]
+
+/// A seedable SplitMix64 generator; small and reproducible rather than
+/// cryptographically sound.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Construct the generator from a seed.
+    pub fn seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Draw the next `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Draw uniformly from `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u32 << 24) as f32
+    }
+}
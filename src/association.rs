@@ -0,0 +1,322 @@
+//! Evidential data association between tracks and measurements.
+//!
+//! Each measurement is considered over the frame `\{t_1, ..., t_k, new\}`, where bit
+//! `i < k` is track `i` and bit `k` is a new target. Each track provides a piece of
+//! evidence from its distance `d` to the measurement, with `\phi = e^{-\gamma d^2}`:
+//! `m(\{t_i\}) = \alpha \phi`, `m(\Omega \setminus \{t_i\}) = \alpha (1 - \phi)` and
+//! the rest upon `\Omega`. These are fused via `Dempster`, so a measurement far from
+//! every track leaves its mass upon `new`.
+//!
+//! The assignment maximizes the product of the pignistic probabilities of the chosen
+//! hypotheses, such that each track takes at most one measurement while any number of
+//! them may start new targets. It's solved exactly as a linear assignment problem, via
+//! the Hungarian algorithm, with a `new` column set aside for each measurement; unlike
+//! a greedy, best-first pass, a confident early choice can't strand a later one.
+//!
+//! The paper below likewise poses association as a linear assignment problem, there
+//! over plausibilities rather than pignistic probabilities.
+//!
+//! T. Denœux, N. El Zoghby, V. Cherfaoui, A. Jouglet, "Optimal object association in
+//! the Dempster-Shafer framework", IEEE Transactions on Cybernetics, 2014.
+use core::ops::{Index, IndexMut};
+use dst::approx::KX;
+use dst::comb::{CombError, Dempster};
+use dst::dst::{bel, betp, comb_approx, pl};
+
+/// The hypothesis a measurement is assigned to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    /// An existing track, by index.
+    Track(usize),
+    /// A new target.
+    New,
+}
+
+/// The assignment of a single measurement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Assignment {
+    /// The hypothesis assigned.
+    pub target: Target,
+    /// Its pignistic probability.
+    pub betp: f32,
+    /// Whether another hypothesis remains as plausible as the assigned one is
+    /// believed; i.e., their belief intervals overlap.
+    pub ambiguous: bool,
+}
+
+/// Evidential data association with distance-based evidence.
+#[derive(Debug)]
+pub struct EvidentialAssociation {
+    alpha: f32,
+    gamma: f32,
+}
+
+impl EvidentialAssociation {
+    /// Construct the association; `alpha` in `(0, 1)` caps the mass a track can
+    /// contribute, and `gamma` is the rate at which it decays with distance.
+    pub fn build(alpha: f32, gamma: f32) -> Self {
+        assert!(alpha > 0f32 && alpha < 1f32);
+        assert!(gamma > 0f32);
+        Self { alpha, gamma }
+    }
+
+    /// Compute the fused BBA of a measurement over the tracks and a new target,
+    /// approximated to `N` elements.
+    pub fn bba<const N: usize, const D: usize>(
+        &self,
+        tracks: &[[f32; D]],
+        z: &[f32; D],
+    ) -> Result<[(usize, f32); N], CombError> {
+        let k = tracks.len();
+        assert!(k < usize::BITS as usize);
+        let frame = usize::MAX >> (usize::BITS as usize - 1 - k);
+
+        if k == 0 {
+            // Without any tracks, it can only be a new target.
+            return Ok(core::array::from_fn(|i| {
+                if i == 0 {
+                    (frame, 1f32)
+                } else {
+                    (0, 0f32)
+                }
+            }));
+        }
+
+        let evidence = tracks.iter().enumerate().map(|(i, x)| {
+            let d2: f32 = x.iter().zip(z).map(|(a, b)| (a - b) * (a - b)).sum();
+            let phi = libm::expf(-self.gamma * d2);
+            [
+                (1usize << i, self.alpha * phi),
+                (frame & !(1 << i), self.alpha * (1f32 - phi)),
+                (frame, 1f32 - self.alpha),
+            ]
+        });
+
+        comb_approx::<N, usize, f32, KX, Dempster>(evidence)
+    }
+
+    /// Assign each of the `M` measurements to a track or a new target; each BBA is
+    /// approximated to `N` elements.
+    pub fn associate<const N: usize, const M: usize, const D: usize>(
+        &self,
+        tracks: &[[f32; D]],
+        measurements: &[[f32; D]; M],
+    ) -> Result<[Assignment; M], CombError> {
+        let k = tracks.len();
+        let mut bba = [[(0usize, 0f32); N]; M];
+        for (m, z) in bba.iter_mut().zip(measurements) {
+            *m = self.bba(tracks, z)?;
+        }
+
+        // Hypothesis `k` is the new target.
+        let target = |h: usize| {
+            if h == k {
+                Target::New
+            } else {
+                Target::Track(h)
+            }
+        };
+
+        // Column `c < k` is track `c`, and column `k + j` is measurement `j` starting a
+        // new target; no other measurement may take it.
+        let hypothesis = |j: usize, c: usize| match c {
+            c if c < k => Some(c),
+            c if c == k + j => Some(k),
+            _ => None,
+        };
+        let cost = |j: usize, c: usize| match hypothesis(j, c) {
+            Some(h) => -libm::logf(f32::max(betp(&bba[j], &(1 << h)), f32::MIN_POSITIVE)),
+            None => FORBIDDEN,
+        };
+
+        let mut assigned: [Option<Assignment>; M] = [None; M];
+        for (j, c) in hungarian::<M>(k + M, cost) {
+            let h = hypothesis(j, c).expect("A new target is always available.");
+            let b = bel(&bba[j], &(1 << h));
+            let ambiguous = (0..=k)
+                .filter(|x| *x != h)
+                .any(|x| pl(&bba[j], &(1usize << x)) >= b);
+            assigned[j] = Some(Assignment {
+                target: target(h),
+                betp: betp(&bba[j], &(1 << h)),
+                ambiguous,
+            });
+        }
+
+        Ok(assigned.map(|x| x.expect("Every measurement is assigned.")))
+    }
+}
+
+/// The cost of a pairing that isn't allowed; large, but finite so that the potentials
+/// stay well-defined.
+const FORBIDDEN: f32 = 1e6;
+
+/// A buffer of `65 + M` elements, indexed contiguously: room for the `k < 64` tracks
+/// and `M` new targets, alongside the Hungarian algorithm's sentinel at `0`.
+struct Buf<T, const M: usize>([T; 65], [T; M]);
+
+impl<T: Copy, const M: usize> Buf<T, M> {
+    fn new(x: T) -> Self {
+        Self([x; 65], [x; M])
+    }
+}
+
+impl<T, const M: usize> Index<usize> for Buf<T, M> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        if i < 65 {
+            &self.0[i]
+        } else {
+            &self.1[i - 65]
+        }
+    }
+}
+
+impl<T, const M: usize> IndexMut<usize> for Buf<T, M> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        if i < 65 {
+            &mut self.0[i]
+        } else {
+            &mut self.1[i - 65]
+        }
+    }
+}
+
+/// Solve the assignment of `M` rows to `cols >= M` columns of least total cost, in
+/// `O(M^2 cols)`; produce the (row, column) pairs.
+///
+/// H. W. Kuhn, "The Hungarian method for the assignment problem", Naval Research
+/// Logistics Quarterly, 1955; here in its shortest augmenting path form.
+fn hungarian<const M: usize>(
+    cols: usize,
+    cost: impl Fn(usize, usize) -> f32,
+) -> impl Iterator<Item = (usize, usize)> {
+    assert!(M <= cols && cols < 65 + M);
+
+    // One-based, with row and column `0` as the sentinel; `p[c]` is the row of column `c`.
+    let (mut u, mut v) = (Buf::<f32, M>::new(0f32), Buf::<f32, M>::new(0f32));
+    let (mut p, mut way) = (Buf::<usize, M>::new(0), Buf::<usize, M>::new(0));
+
+    for row in 1..=M {
+        p[0] = row;
+        let mut c0 = 0usize;
+        let mut min = Buf::<f32, M>::new(f32::INFINITY);
+        let mut used = Buf::<bool, M>::new(false);
+
+        // Grow the shortest augmenting path from `row` until it reaches a free column.
+        loop {
+            used[c0] = true;
+            let (r0, mut delta, mut c1) = (p[c0], f32::INFINITY, 0usize);
+            for c in (1..=cols).filter(|c| !used[*c]) {
+                let reduced = cost(r0 - 1, c - 1) - u[r0] - v[c];
+                if reduced < min[c] {
+                    (min[c], way[c]) = (reduced, c0);
+                }
+                if min[c] < delta {
+                    (delta, c1) = (min[c], c);
+                }
+            }
+            for c in 0..=cols {
+                if used[c] {
+                    u[p[c]] += delta;
+                    v[c] -= delta;
+                } else {
+                    min[c] -= delta;
+                }
+            }
+
+            c0 = c1;
+            if p[c0] == 0 {
+                break;
+            }
+        }
+
+        // Flip the path.
+        while c0 != 0 {
+            let c1 = way[c0];
+            p[c0] = p[c1];
+            c0 = c1;
+        }
+    }
+
+    (1..=cols).filter_map(move |c| p[c].checked_sub(1).map(|r| (r, c - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACKS: [[f32; 2]; 3] = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]];
+
+    #[test]
+    fn test_associate() {
+        let assoc = EvidentialAssociation::build(0.9, 0.5);
+        let z = [[9.8, 0.3], [0.2, -0.1], [50.0, 50.0]];
+        let a = assoc.associate::<8, 3, 2>(&TRACKS, &z).unwrap();
+
+        assert_eq!(a[0].target, Target::Track(1));
+        assert_eq!(a[1].target, Target::Track(0));
+        assert_eq!(a[2].target, Target::New);
+        assert!(!a[0].ambiguous && !a[1].ambiguous);
+    }
+
+    #[test]
+    fn test_associate_consistent() {
+        // Both measurements are nearest track 0, but only one may take it.
+        let assoc = EvidentialAssociation::build(0.9, 0.5);
+        let z = [[0.1, 0.0], [0.6, 0.2]];
+        let a = assoc.associate::<8, 2, 2>(&TRACKS, &z).unwrap();
+
+        assert_eq!(a[0].target, Target::Track(0));
+        assert_eq!(a[1].target, Target::New);
+    }
+
+    #[test]
+    fn test_associate_ambiguous() {
+        // Equidistant between tracks 0 and 1, and close enough to either.
+        let assoc = EvidentialAssociation::build(0.9, 0.01);
+        let a = assoc.associate::<8, 1, 2>(&TRACKS, &[[5.0, 0.0]]).unwrap();
+        assert!(matches!(a[0].target, Target::Track(0) | Target::Track(1)));
+        assert!(a[0].ambiguous);
+    }
+
+    #[test]
+    fn test_associate_optimal() {
+        // Measurement 1 is the nearest to track 0, so a greedy pass would give it track
+        // 0 and leave measurement 0 a new target; jointly, 0 -> 0 and 1 -> 1 is better.
+        let assoc = EvidentialAssociation::build(0.9, 0.1);
+        let tracks = [[0.0, 0.0], [2.0, 0.0]];
+        let z = [[-1.5, 0.0], [0.5, 0.0]];
+
+        let (b0, b1) = (
+            assoc.bba::<8, 2>(&tracks, &z[0]).unwrap(),
+            assoc.bba::<8, 2>(&tracks, &z[1]).unwrap(),
+        );
+        assert!(betp(&b1, &0b001) > betp(&b0, &0b001));
+        assert!(betp(&b1, &0b001) > betp(&b1, &0b010));
+        assert!(betp(&b0, &0b100) > betp(&b0, &0b010));
+
+        let a = assoc.associate::<8, 2, 2>(&tracks, &z).unwrap();
+        assert_eq!(a[0].target, Target::Track(0));
+        assert_eq!(a[1].target, Target::Track(1));
+    }
+
+    #[test]
+    fn test_hungarian() {
+        // Greedy would take the 1.0 and then be left with 9.0.
+        let cost = [[2.0f32, 3.0], [1.0, 9.0]];
+        let mut pairs: Vec<_> = hungarian::<2>(2, |r, c| cost[r][c]).collect();
+        pairs.sort();
+        assert_eq!(pairs, [(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn test_no_tracks() {
+        let assoc = EvidentialAssociation::build(0.9, 0.5);
+        let a = assoc
+            .associate::<2, 2, 2>(&[], &[[0.0, 0.0], [1.0, 1.0]])
+            .unwrap();
+        assert!(a.iter().all(|x| x.target == Target::New));
+    }
+}
@@ -0,0 +1,5 @@
+//! Glue between the fusion crates and the tracking side.
+#![cfg_attr(not(test), no_std)]
+#![warn(missing_docs)]
+
+pub mod association;